@@ -0,0 +1,88 @@
+use crate::settings::Settings;
+use bevy::prelude::*;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum AudioBus {
+    Music,
+    Sfx,
+    Voice,
+}
+
+impl AudioBus {
+    pub fn volume(&self, settings: &Settings) -> f32 {
+        settings.master_volume
+            * match self {
+                Self::Music => settings.music_volume,
+                Self::Sfx => settings.sfx_volume,
+                Self::Voice => settings.voice_volume,
+            }
+    }
+}
+
+/// What a sound is, which decides its bus, how many of it can play at once
+/// and whether it ducks the music
+#[derive(Component, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum SoundCategory {
+    Music,
+    Ambience,
+    Footsteps,
+    Effect,
+    Scare,
+    Radio,
+}
+
+impl SoundCategory {
+    pub fn bus(&self) -> AudioBus {
+        match self {
+            Self::Music => AudioBus::Music,
+            Self::Radio => AudioBus::Voice,
+            _ => AudioBus::Sfx,
+        }
+    }
+
+    pub fn max_voices(&self) -> usize {
+        match self {
+            Self::Music | Self::Radio => 1,
+            Self::Ambience | Self::Footsteps => 2,
+            Self::Scare => 3,
+            Self::Effect => 8,
+        }
+    }
+
+    pub fn ducks_music(&self) -> bool {
+        matches!(self, Self::Scare | Self::Radio)
+    }
+}
+
+/// A positional sound that fades out with its distance to the listener,
+/// tuned so that it carries a few floors up or down the stairwell
+#[derive(Component, Clone, Copy, Debug)]
+pub struct SoundEmitter {
+    pub volume: f32,
+    /// Heard at full volume within this distance
+    pub reference_distance: f32,
+    /// Not heard at all past this distance
+    pub max_distance: f32,
+    pub rolloff: f32,
+}
+
+impl Default for SoundEmitter {
+    fn default() -> Self {
+        Self {
+            volume: 1.0,
+            reference_distance: 1.0,
+            max_distance: 8.0,
+            rolloff: 1.0,
+        }
+    }
+}
+
+impl SoundEmitter {
+    pub fn gain(&self, distance: f32) -> f32 {
+        if distance >= self.max_distance {
+            return 0.0;
+        }
+
+        (self.reference_distance / distance.max(self.reference_distance)).powf(self.rolloff)
+    }
+}
@@ -0,0 +1,30 @@
+use crate::AppState;
+use bevy::{prelude::*, transform::TransformSystem};
+use resources::{Ambience, MusicDucking};
+use systems::*;
+
+pub mod components;
+pub mod resources;
+pub mod systems;
+#[cfg(test)]
+mod tests;
+
+pub struct SoundPlugin;
+
+impl Plugin for SoundPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<MusicDucking>()
+            .init_resource::<Ambience>()
+            .add_systems(OnEnter(AppState::Game), spawn_music)
+            .add_systems(Update, schedule_ambience.run_if(in_state(AppState::Game)))
+            // Not tied to the game state, the death sound plays over the death screen.
+            // Bevy plays the queued sounds after the transforms are propagated
+            .add_systems(
+                PostUpdate,
+                (
+                    limit_voices.before(TransformSystem::TransformPropagate),
+                    update_volumes.after(TransformSystem::TransformPropagate),
+                ),
+            );
+    }
+}
@@ -0,0 +1,35 @@
+use bevy::prelude::*;
+use rand::prelude::*;
+use std::ops::Range;
+
+/// Music volume multiplier, eased down while scares and radio lines play
+#[derive(Resource)]
+pub struct MusicDucking(pub f32);
+
+impl Default for MusicDucking {
+    fn default() -> Self {
+        Self(1.0)
+    }
+}
+
+/// Plays an ambient sound every `interval` seconds, picked at random each time
+#[derive(Resource)]
+pub struct Ambience {
+    pub interval: Range<f32>,
+    pub timer: Timer,
+}
+
+impl Default for Ambience {
+    fn default() -> Self {
+        Self {
+            interval: 4.0..14.0,
+            timer: Timer::from_seconds(8.0, TimerMode::Once),
+        }
+    }
+}
+
+impl Ambience {
+    pub fn reschedule(&mut self, rng: &mut impl Rng) {
+        self.timer = Timer::from_seconds(rng.gen_range(self.interval.clone()), TimerMode::Once);
+    }
+}
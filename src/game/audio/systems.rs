@@ -0,0 +1,174 @@
+use super::{
+    components::{SoundCategory, SoundEmitter},
+    resources::{Ambience, MusicDucking},
+};
+use crate::{
+    game::player::components::PlayerCamera, resources::AudioAssets, settings::Settings, AppState,
+};
+use bevy::{ecs::system::EntityCommands, prelude::*};
+use bevy_rand::prelude::*;
+use rand::prelude::*;
+use std::collections::HashMap;
+
+/// Music volume while it is ducked
+const DUCKED_VOLUME: f32 = 0.3;
+
+/// Plays a one-shot sound that isn't placed in the world
+pub fn play_sound<'a>(
+    commands: &'a mut Commands,
+    category: SoundCategory,
+    source: Handle<AudioSource>,
+) -> EntityCommands<'a> {
+    commands.spawn((
+        AudioBundle {
+            source,
+            settings: PlaybackSettings::DESPAWN,
+        },
+        category,
+        StateScoped(AppState::Game),
+    ))
+}
+
+/// Plays a one-shot sound from a world position
+pub fn play_sound_at<'a>(
+    commands: &'a mut Commands,
+    category: SoundCategory,
+    source: Handle<AudioSource>,
+    position: Vec3,
+) -> EntityCommands<'a> {
+    commands.spawn((
+        AudioBundle {
+            source,
+            settings: PlaybackSettings::DESPAWN.with_spatial(true),
+        },
+        TransformBundle::from_transform(Transform::from_translation(position)),
+        category,
+        SoundEmitter::default(),
+        StateScoped(AppState::Game),
+    ))
+}
+
+pub fn spawn_music(mut commands: Commands, audio_assets: Res<AudioAssets>) {
+    commands.spawn((
+        AudioBundle {
+            source: audio_assets.music.clone(),
+            settings: PlaybackSettings::LOOP,
+        },
+        SoundCategory::Music,
+        StateScoped(AppState::Game),
+    ));
+}
+
+pub fn schedule_ambience(
+    time: Res<Time>,
+    mut commands: Commands,
+    audio_assets: Res<AudioAssets>,
+    mut ambience: ResMut<Ambience>,
+    query: Query<&GlobalTransform, With<PlayerCamera>>,
+    mut rng: ResMut<GlobalEntropy<WyRand>>,
+) {
+    if !ambience.timer.tick(time.delta()).finished() {
+        return;
+    }
+    ambience.reschedule(&mut *rng);
+
+    let Ok(c_transform) = query.get_single() else {
+        return;
+    };
+
+    if rng.gen_range(0..10) == 0 {
+        // Right behind the player's back
+        let position = c_transform.translation() - c_transform.forward() * 1.5;
+        play_sound_at(
+            &mut commands,
+            SoundCategory::Scare,
+            audio_assets.behind_sfx.clone(),
+            position,
+        );
+    } else {
+        // Somewhere up or down the stairwell
+        let offset = Vec3::new(
+            rng.gen_range(-1.0..1.0),
+            rng.gen_range(-6.0..6.0),
+            rng.gen_range(-1.0..1.0),
+        );
+        play_sound_at(
+            &mut commands,
+            SoundCategory::Ambience,
+            audio_assets.ambient_sfx[rng.gen_range(0..audio_assets.ambient_sfx.len())].clone(),
+            c_transform.translation() + offset,
+        );
+    }
+}
+
+/// Drops new sounds of a category that is already playing as many as it may
+pub fn limit_voices(mut commands: Commands, query: Query<(Entity, Ref<SoundCategory>)>) {
+    let mut voices: HashMap<SoundCategory, usize> = HashMap::new();
+
+    for (_, category) in &query {
+        if !category.is_added() {
+            *voices.entry(*category).or_default() += 1;
+        }
+    }
+
+    for (entity, category) in &query {
+        if !category.is_added() {
+            continue;
+        }
+
+        let count = voices.entry(*category).or_default();
+        if *count < category.max_voices() {
+            *count += 1;
+        } else {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+pub fn update_volumes(
+    time: Res<Time>,
+    settings: Res<Settings>,
+    mut ducking: ResMut<MusicDucking>,
+    l_query: Query<&GlobalTransform, With<SpatialListener>>,
+    sinks: Query<(&AudioSink, &SoundCategory)>,
+    spatial_sinks: Query<(
+        &SpatialAudioSink,
+        &SoundCategory,
+        &SoundEmitter,
+        &GlobalTransform,
+    )>,
+) {
+    let ducked = sinks
+        .iter()
+        .any(|(sink, category)| category.ducks_music() && !sink.empty())
+        || spatial_sinks
+            .iter()
+            .any(|(sink, category, ..)| category.ducks_music() && !sink.empty());
+
+    let target = if ducked { DUCKED_VOLUME } else { 1.0 };
+    ducking.0 = ducking
+        .0
+        .lerp(target, (time.delta_seconds() * 4.0).min(1.0));
+
+    let music_ducking = ducking.0;
+    let volume = |category: &SoundCategory| {
+        let ducking = if *category == SoundCategory::Music {
+            music_ducking
+        } else {
+            1.0
+        };
+        category.bus().volume(&settings) * ducking
+    };
+
+    for (sink, category) in &sinks {
+        sink.set_volume(volume(category));
+    }
+
+    let listener = l_query.get_single().map(GlobalTransform::translation).ok();
+    for (sink, category, emitter, s_transform) in &spatial_sinks {
+        let gain = listener.map_or(1.0, |listener| {
+            emitter.gain(listener.distance(s_transform.translation()))
+        });
+        sink.set_volume(emitter.volume * gain * volume(category));
+    }
+}
@@ -0,0 +1,89 @@
+use super::{
+    components::{AudioBus, SoundCategory, SoundEmitter},
+    systems::limit_voices,
+};
+use crate::{game::testing::test_app, settings::Settings};
+use bevy::prelude::*;
+
+fn voices(app: &mut App, category: SoundCategory) -> usize {
+    let world = app.world_mut();
+    world
+        .query::<&SoundCategory>()
+        .iter(world)
+        .filter(|&&c| c == category)
+        .count()
+}
+
+#[test]
+fn voices_over_the_cap_are_dropped() {
+    let mut app = test_app();
+    app.add_systems(PostUpdate, limit_voices);
+
+    for _ in 0..3 {
+        app.world_mut().spawn(SoundCategory::Footsteps);
+    }
+    app.world_mut().spawn(SoundCategory::Effect);
+    app.update();
+    assert_eq!(voices(&mut app, SoundCategory::Footsteps), 2);
+    assert_eq!(voices(&mut app, SoundCategory::Effect), 1);
+
+    // The voices already playing are kept, the new one is dropped
+    let late = app.world_mut().spawn(SoundCategory::Footsteps).id();
+    app.update();
+    assert!(app.world().get_entity(late).is_none());
+    assert_eq!(voices(&mut app, SoundCategory::Footsteps), 2);
+
+    // Once one ends there is room again
+    let world = app.world_mut();
+    let playing = world
+        .query::<(Entity, &SoundCategory)>()
+        .iter(world)
+        .find(|(_, &category)| category == SoundCategory::Footsteps)
+        .map(|(entity, _)| entity)
+        .unwrap();
+    world.despawn(playing);
+    let next = world.spawn(SoundCategory::Footsteps).id();
+    app.update();
+    assert!(app.world().get_entity(next).is_some());
+    assert_eq!(voices(&mut app, SoundCategory::Footsteps), 2);
+}
+
+#[test]
+fn gain_falls_off_with_distance() {
+    let emitter = SoundEmitter::default();
+    assert_eq!(emitter.gain(0.0), 1.0);
+    assert_eq!(emitter.gain(emitter.reference_distance), 1.0);
+    assert_eq!(emitter.gain(emitter.max_distance), 0.0);
+
+    let mut last = 1.0;
+    for step in 0..40 {
+        let gain = emitter.gain(step as f32 * 0.25);
+        assert!(gain <= last, "louder at {} metres", step as f32 * 0.25);
+        last = gain;
+    }
+}
+
+#[test]
+fn gain_never_goes_above_the_bus_volume() {
+    let settings = Settings {
+        master_volume: 0.8,
+        sfx_volume: 0.5,
+        ..default()
+    };
+    let bus = AudioBus::Sfx.volume(&settings);
+
+    for emitter in [
+        SoundEmitter::default(),
+        SoundEmitter {
+            reference_distance: 0.5,
+            rolloff: 2.0,
+            ..default()
+        },
+    ] {
+        for step in 0..40 {
+            let gain = emitter.gain(step as f32 * 0.25);
+            assert!((0.0..=1.0).contains(&gain));
+            assert!(emitter.volume * gain * bus <= bus);
+        }
+    }
+}
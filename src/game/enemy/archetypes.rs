@@ -0,0 +1,44 @@
+use super::components::{AiBehaviour, AnimationSet, EnemyCues};
+use bevy::prelude::*;
+use serde::Deserialize;
+use std::collections::HashMap;
+
+/// Every kind of enemy, by id, loaded from `map/enemies.archetypes.ron`
+#[derive(Asset, TypePath, Deserialize, Debug)]
+pub struct EnemyArchetypes {
+    pub archetypes: HashMap<String, EnemyArchetype>,
+}
+
+impl EnemyArchetypes {
+    pub fn get(&self, id: &str) -> Option<&EnemyArchetype> {
+        self.archetypes.get(id)
+    }
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct EnemyArchetype {
+    /// glTF file of the model, relative to `assets`
+    pub model: String,
+    pub scale: f32,
+    /// Offset of the model from the centre of the collider
+    #[serde(default)]
+    pub model_offset: Vec3,
+    pub animations: AnimationSet,
+    pub collider: CapsuleSize,
+    /// Top speed in metres per second, floor events may override it
+    pub speed: f32,
+    /// Kills the player once closer than this, touching them always does
+    #[serde(default)]
+    pub kill_radius: f32,
+    #[serde(default)]
+    pub cues: EnemyCues,
+    #[serde(default)]
+    pub behaviour: AiBehaviour,
+}
+
+#[derive(Deserialize, Clone, Copy, Debug)]
+pub struct CapsuleSize {
+    pub radius: f32,
+    /// Length of the cylinder between the two caps
+    pub length: f32,
+}
@@ -1,19 +1,215 @@
-use bevy::prelude::*;
+use crate::game::map::events::FloorSound;
+use bevy::{prelude::*, utils::HashMap};
+use serde::Deserialize;
+use std::time::Duration;
 
 #[derive(Component)]
 pub struct Enemy {
+    /// Top speed in metres per second
+    pub speed: f32,
+    /// Metres from the player at which they die, on top of contact
+    pub kill_radius: f32,
+}
+
+/// Sounds an enemy makes, see [`FloorSound`]
+#[derive(Component, Deserialize, Default, Clone, Copy, Debug)]
+pub struct EnemyCues {
+    /// Played where the enemy spawns
+    #[serde(default)]
+    pub spawn: Option<FloorSound>,
+    /// Played from the enemy when it starts chasing the player
+    #[serde(default)]
+    pub chase: Option<FloorSound>,
+}
+
+#[derive(Default, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum AiState {
+    /// Stands still until it sees the player
+    #[default]
+    Idle,
+    /// Follows the player at a distance
+    Stalk,
+    /// Runs at the player
+    Chase,
+    /// Runs away down the stairwell
+    Flee,
+    /// Done fleeing, despawns
+    Vanish,
+}
+
+/// How an enemy reacts to the player, distances are in metres and times in seconds
+#[derive(Deserialize, Clone, Copy, Debug)]
+#[serde(default)]
+pub struct AiBehaviour {
+    /// Metres per second squared
+    pub acceleration: f32,
+    pub sight_range: f32,
+    /// Distance kept from the player while stalking
+    pub stalk_distance: f32,
+    /// Time spent stalking before giving chase
+    pub stalk_time: f32,
+    /// Time without seeing the player before giving up, never gives up if `None`
+    pub lose_time: Option<f32>,
+    /// Flees from a lit lighter closer than this
+    pub fear_of_light: Option<f32>,
+    /// Time spent fleeing before vanishing
+    pub flee_time: f32,
+    /// Only moves while it is outside the player's view
+    pub freeze_in_view: bool,
+}
+
+impl Default for AiBehaviour {
+    fn default() -> Self {
+        Self {
+            acceleration: 6.0,
+            sight_range: 8.0,
+            stalk_distance: 3.0,
+            stalk_time: 4.0,
+            lose_time: Some(3.0),
+            fear_of_light: Some(1.5),
+            flee_time: 2.0,
+            freeze_in_view: false,
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct EnemyAi {
+    pub state: AiState,
+    pub behaviour: AiBehaviour,
+    /// Seconds spent in the current state
+    pub elapsed: f32,
+    /// Seconds since the player was last seen
+    pub unseen: f32,
+    /// Whether the current state was entered this frame
+    pub entered: bool,
+}
+
+impl EnemyAi {
+    pub fn new(behaviour: AiBehaviour) -> Self {
+        Self {
+            state: AiState::Idle,
+            behaviour,
+            elapsed: 0.0,
+            unseen: 0.0,
+            entered: false,
+        }
+    }
+
+    pub fn set_state(&mut self, state: AiState) {
+        if self.state != state {
+            self.state = state;
+            self.elapsed = 0.0;
+            self.entered = true;
+        }
+    }
+}
+
+#[derive(Deserialize, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum EnemyAnimation {
+    Idle,
+    Walk,
+    Run,
+    Attack,
+    Crawl,
+}
+
+impl EnemyAnimation {
+    /// Played instead when a model has no clip for this one
+    pub fn fallback(&self) -> Option<Self> {
+        match self {
+            Self::Run | Self::Crawl => Some(Self::Walk),
+            Self::Walk | Self::Attack => Some(Self::Idle),
+            Self::Idle => None,
+        }
+    }
+}
+
+/// A clip of an enemy model's glTF file
+#[derive(Deserialize, Clone, Copy, Debug)]
+pub struct AnimationClipInfo {
+    pub index: usize,
+    /// Movement speed in metres per second the clip plays at normal speed at,
+    /// zero if it is played in place
+    #[serde(default)]
+    pub speed: f32,
+}
+
+/// The named clips of an enemy model
+#[derive(Deserialize, Clone, Debug)]
+pub struct AnimationSet {
+    pub clips: HashMap<EnemyAnimation, AnimationClipInfo>,
+    /// Seconds to cross-fade from one clip to the next
+    pub crossfade: f32,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct EnemyAnimationNode {
+    pub index: AnimationNodeIndex,
+    /// See [`AnimationClipInfo::speed`]
     pub speed: f32,
 }
 
 #[derive(Component)]
 pub struct EnemyAnimations {
-    pub animations: Vec<AnimationNodeIndex>,
-    #[allow(dead_code)]
+    pub nodes: HashMap<EnemyAnimation, EnemyAnimationNode>,
+    pub crossfade: Duration,
     pub graph: Handle<AnimationGraph>,
+    /// The model's `AnimationPlayer`, once its scene has spawned
+    pub player: Option<Entity>,
+    pub current: Option<EnemyAnimation>,
+}
+
+impl EnemyAnimations {
+    /// Builds the animation graph of `set`, `clip` gives the clip of the
+    /// model's glTF file at an index
+    pub fn new(
+        set: &AnimationSet,
+        clip: impl Fn(usize) -> Handle<AnimationClip>,
+        graphs: &mut Assets<AnimationGraph>,
+    ) -> Self {
+        let mut graph = AnimationGraph::new();
+        let root = graph.root;
+
+        let nodes = set
+            .clips
+            .iter()
+            .map(|(&name, info)| {
+                let node = EnemyAnimationNode {
+                    index: graph.add_clip(clip(info.index), 1.0, root),
+                    speed: info.speed,
+                };
+                (name, node)
+            })
+            .collect();
+
+        Self {
+            nodes,
+            crossfade: Duration::from_secs_f32(set.crossfade),
+            graph: graphs.add(graph),
+            player: None,
+            current: None,
+        }
+    }
+
+    /// The node to play for `animation`, or for the closest fallback the model has
+    pub fn node(&self, animation: EnemyAnimation) -> Option<(EnemyAnimation, EnemyAnimationNode)> {
+        let mut animation = Some(animation);
+        while let Some(name) = animation {
+            if let Some(&node) = self.nodes.get(&name) {
+                return Some((name, node));
+            }
+            animation = name.fallback();
+        }
+        None
+    }
 }
 
 impl Default for Enemy {
     fn default() -> Self {
-        Self { speed: 0.01 }
+        Self {
+            speed: 1.5,
+            kill_radius: 0.0,
+        }
     }
 }
@@ -1,23 +1,43 @@
 use crate::AppState;
+use archetypes::EnemyArchetypes;
 use bevy::{animation::animate_targets, prelude::*};
+use bevy_common_assets::ron::RonAssetPlugin;
+use resources::{EnemyModels, EnemyRegistry};
 use systems::*;
 
+pub mod archetypes;
 pub mod components;
+pub mod resources;
 mod systems;
+#[cfg(test)]
+mod tests;
 
 pub struct EnemyPlugin;
 
 impl Plugin for EnemyPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(
-            Update,
-            enemies_once_loaded
-                .before(animate_targets)
-                .run_if(in_state(AppState::Game)),
-        )
-        .add_systems(
-            Update,
-            (enemies_update, enemies_animation).run_if(in_state(AppState::Game)),
-        );
+        app.add_plugins(RonAssetPlugin::<EnemyArchetypes>::new(&["archetypes.ron"]))
+            .init_resource::<EnemyRegistry>()
+            .init_resource::<EnemyModels>()
+            // The asset collections, and with them the archetypes, are loaded by then
+            .add_systems(OnExit(AppState::None), load_enemy_models)
+            .add_systems(OnExit(AppState::Game), enemies_despawn)
+            .add_systems(
+                Update,
+                enemies_once_loaded
+                    .before(animate_targets)
+                    .run_if(in_state(AppState::Game)),
+            )
+            .add_systems(
+                Update,
+                (
+                    enemies_ai,
+                    enemies_cues.after(enemies_ai),
+                    enemies_animation,
+                    enemies_lifetime,
+                    enemies_cull,
+                )
+                    .run_if(in_state(AppState::Game)),
+            );
     }
 }
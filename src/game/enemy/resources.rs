@@ -0,0 +1,73 @@
+use crate::game::map::resources::FloorAction;
+use bevy::{asset::RecursiveDependencyLoadState, gltf::Gltf, prelude::*};
+use std::collections::HashMap;
+
+/// Where an enemy came from and how long it has left
+#[derive(Debug)]
+pub struct EnemyRecord {
+    pub floor: usize,
+    pub action: FloorAction,
+    pub lifetime: Option<Timer>,
+}
+
+/// Every live enemy spawned by a floor event
+#[derive(Resource, Default, Debug)]
+pub struct EnemyRegistry {
+    pub enemies: HashMap<Entity, EnemyRecord>,
+}
+
+impl EnemyRegistry {
+    pub fn register(&mut self, entity: Entity, record: EnemyRecord) {
+        self.enemies.insert(entity, record);
+    }
+
+    pub fn despawn(&mut self, commands: &mut Commands, entity: Entity) {
+        self.enemies.remove(&entity);
+        despawn_enemy(commands, entity);
+    }
+
+    /// Despawns every enemy the given floor's event spawned
+    pub fn despawn_floor(&mut self, commands: &mut Commands, floor: usize) {
+        self.despawn_where(commands, |record| record.floor == floor);
+    }
+
+    pub fn despawn_where(
+        &mut self,
+        commands: &mut Commands,
+        mut predicate: impl FnMut(&mut EnemyRecord) -> bool,
+    ) {
+        self.enemies.retain(|&entity, record| {
+            if predicate(record) {
+                despawn_enemy(commands, entity);
+                false
+            } else {
+                true
+            }
+        });
+    }
+}
+
+fn despawn_enemy(commands: &mut Commands, entity: Entity) {
+    // The enemy may already be gone, e.g. if it was despawned with its state
+    if let Some(enemy) = commands.get_entity(entity) {
+        enemy.despawn_recursive();
+    }
+}
+
+/// Keeps the model of every archetype loaded, so enemies don't pop in when they spawn
+#[derive(Resource, Default, Debug)]
+pub struct EnemyModels {
+    pub models: Vec<Handle<Gltf>>,
+}
+
+impl EnemyModels {
+    /// Whether every model is done loading, a model that failed to load is logged and skipped
+    pub fn is_loaded(&self, asset_server: &AssetServer) -> bool {
+        self.models.iter().all(|model| {
+            matches!(
+                asset_server.get_recursive_dependency_load_state(model),
+                Some(RecursiveDependencyLoadState::Loaded | RecursiveDependencyLoadState::Failed)
+            )
+        })
+    }
+}
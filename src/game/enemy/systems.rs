@@ -1,72 +1,363 @@
-use super::components::{Enemy, EnemyAnimations};
-use crate::game::player::components::Player;
+use super::{
+    archetypes::EnemyArchetypes,
+    components::{AiState, Enemy, EnemyAi, EnemyAnimation, EnemyAnimations, EnemyCues},
+    resources::{EnemyModels, EnemyRegistry},
+};
+use crate::{
+    game::{
+        audio::systems::play_sound_at,
+        map::resources::{FloorPoints, ObjectPool},
+        player::components::{Lighter, Player, PlayerCamera},
+    },
+    resources::{AudioAssets, MapAssets},
+};
 use avian3d::prelude::*;
 use bevy::prelude::*;
-use std::time::Duration;
+use bevy_rand::prelude::*;
+use std::{cmp::Ordering, collections::BTreeSet};
 
+/// How close an enemy has to get to a stairwell waypoint before heading for the next one
+const WAYPOINT_RADIUS: f32 = 0.75;
+/// How close an enemy has to be to the player to play its attack
+const ATTACK_DISTANCE: f32 = 1.2;
+
+/// Starts loading the model of every archetype once the archetypes themselves are loaded
+pub fn load_enemy_models(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    map_assets: Res<MapAssets>,
+    archetypes: Res<Assets<EnemyArchetypes>>,
+) {
+    let Some(archetypes) = archetypes.get(&map_assets.enemy_archetypes) else {
+        warn!("The enemy archetypes are not loaded, enemy models load as they spawn");
+        return;
+    };
+
+    // Archetypes may share a model
+    let paths: BTreeSet<_> = archetypes
+        .archetypes
+        .values()
+        .map(|archetype| archetype.model.clone())
+        .collect();
+
+    commands.insert_resource(EnemyModels {
+        models: paths
+            .into_iter()
+            .map(|path| asset_server.load(path))
+            .collect(),
+    });
+}
+
+/// Hooks the `AnimationPlayer` of a freshly spawned enemy model up to its enemy
 pub fn enemies_once_loaded(
     mut commands: Commands,
-    mut players: Query<(Entity, &mut AnimationPlayer, &EnemyAnimations), Added<AnimationPlayer>>,
+    players: Query<Entity, Added<AnimationPlayer>>,
+    parents: Query<&Parent>,
+    mut e_query: Query<&mut EnemyAnimations>,
 ) {
-    for (entity, mut player, animations) in &mut players {
-        let mut transitions = AnimationTransitions::new();
+    for entity in &players {
+        // The player sits somewhere inside the enemy's model scene
+        let Some(enemy) = parents
+            .iter_ancestors(entity)
+            .find(|&ancestor| e_query.contains(ancestor))
+        else {
+            continue;
+        };
+        let Ok(mut animations) = e_query.get_mut(enemy) else {
+            continue;
+        };
 
-        // Make sure to start the animation via the `AnimationTransitions`
-        // component. The `AnimationTransitions` component wants to manage all
-        // the animations and will get confused if the animations are started
-        // directly via the `AnimationPlayer`.
-        transitions
-            .play(&mut player, animations.animations[0], Duration::ZERO)
-            .repeat();
+        animations.player = Some(entity);
+        animations.current = None;
 
+        // The `AnimationTransitions` component wants to manage all the
+        // animations, `enemies_animation` starts the first one through it
         commands
             .entity(entity)
             .insert(animations.graph.clone())
-            .insert(transitions);
+            .insert(AnimationTransitions::new());
     }
 }
 
-pub fn enemies_update(
-    mut e_query: Query<(&mut Transform, &mut LinearVelocity, &Visibility, &Enemy), Without<Player>>,
-    p_query: Query<&Transform, (With<Player>, Without<Enemy>)>,
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+pub fn enemies_ai(
+    time: Res<Time>,
+    spatial_query: SpatialQuery,
+    mut commands: Commands,
+    mut registry: ResMut<EnemyRegistry>,
+    mut e_query: Query<
+        (
+            Entity,
+            &mut Transform,
+            &mut LinearVelocity,
+            &mut EnemyAi,
+            &Enemy,
+        ),
+        Without<Player>,
+    >,
+    p_query: Query<(Entity, &Transform), With<Player>>,
+    c_query: Query<(&Camera, &GlobalTransform), With<PlayerCamera>>,
+    l_query: Query<(&Lighter, &GlobalTransform)>,
 ) {
-    if let Ok(p_transform) = p_query.get_single() {
-        for (mut e_transform, mut linear_velocity, visibility, enemy) in &mut e_query {
-            if *visibility != Visibility::Hidden {
-                e_transform.look_at(p_transform.translation, Vec3::Y);
-                let direction = p_transform.translation - e_transform.translation;
-                if direction.length() > 1.5 {
-                    let movement_direction = direction.normalize();
-                    linear_velocity.0 += movement_direction * enemy.speed;
-                }
-                // BlurTimer = 200;
+    let (Ok((player, p_transform)), Ok((camera, c_transform))) =
+        (p_query.get_single(), c_query.get_single())
+    else {
+        return;
+    };
+
+    let dt = time.delta_seconds();
+    let eyes = c_transform.translation();
+    let lighter = l_query
+        .iter()
+        .find(|(lighter, _)| lighter.lit)
+        .map(|(_, transform)| transform.translation());
+
+    for (entity, mut e_transform, mut linear_velocity, mut ai, enemy) in &mut e_query {
+        let behaviour = ai.behaviour;
+        let position = e_transform.translation;
+        let head = position + Vec3::Y * 0.5;
+        let distance = p_transform.translation.distance(position);
+
+        let sees_player = distance <= behaviour.sight_range
+            && line_of_sight(&spatial_query, entity, player, head, eyes);
+        let scared = behaviour
+            .fear_of_light
+            .zip(lighter)
+            .is_some_and(|(range, lighter)| lighter.distance(position) < range);
+
+        ai.entered = false;
+        ai.elapsed += dt;
+        if sees_player {
+            ai.unseen = 0.0;
+        } else {
+            ai.unseen += dt;
+        }
+
+        let next_state = match ai.state {
+            AiState::Idle if sees_player => AiState::Stalk,
+            AiState::Stalk | AiState::Chase if scared => AiState::Flee,
+            AiState::Stalk | AiState::Chase
+                if behaviour.lose_time.is_some_and(|secs| ai.unseen > secs) =>
+            {
+                AiState::Idle
+            }
+            AiState::Stalk
+                if ai.elapsed >= behaviour.stalk_time || distance < behaviour.stalk_distance =>
+            {
+                AiState::Chase
+            }
+            AiState::Flee if ai.elapsed >= behaviour.flee_time => AiState::Vanish,
+            state => state,
+        };
+        ai.set_state(next_state);
+
+        let (target, speed) = match ai.state {
+            AiState::Idle => (p_transform.translation, 0.0),
+            AiState::Stalk => (p_transform.translation, enemy.speed * 0.5),
+            AiState::Chase => (p_transform.translation, enemy.speed),
+            // Anywhere far down the stairwell
+            AiState::Flee => (position - Vec3::Y * 10.0, enemy.speed),
+            AiState::Vanish => {
+                registry.despawn(&mut commands, entity);
+                continue;
             }
+        };
+
+        let frozen = behaviour.freeze_in_view
+            && (is_in_view(camera, c_transform, position) || is_in_view(camera, c_transform, head));
+
+        let desired = if frozen || speed <= 0.0 {
+            Vec3::ZERO
+        } else {
+            let waypoint = stairwell_waypoint(position, target);
+            (waypoint - position).with_y(0.0).normalize_or_zero() * speed
+        };
+
+        let acceleration = if frozen {
+            f32::INFINITY
+        } else {
+            behaviour.acceleration * dt
+        };
+        let current = linear_velocity.0.with_y(0.0);
+        let velocity = (current + (desired - current).clamp_length_max(acceleration))
+            .clamp_length_max(enemy.speed);
+        linear_velocity.x = velocity.x;
+        linear_velocity.z = velocity.z;
+
+        if frozen {
+            continue;
         }
+
+        let facing = if velocity.length_squared() > 0.01 {
+            position + velocity
+        } else {
+            p_transform.translation
+        };
+        e_transform.look_at(facing.with_y(position.y), Vec3::Y);
+    }
+}
+
+/// Whether a ray from `from` to `to` hits the player before any level geometry
+fn line_of_sight(
+    spatial_query: &SpatialQuery,
+    enemy: Entity,
+    player: Entity,
+    from: Vec3,
+    to: Vec3,
+) -> bool {
+    let Ok(direction) = Dir3::new(to - from) else {
+        return true;
+    };
+
+    spatial_query
+        .cast_ray(
+            from,
+            direction,
+            from.distance(to),
+            true,
+            SpatialQueryFilter::from_excluded_entities([enemy]),
+        )
+        .is_none_or(|hit| hit.entity == player)
+}
+
+/// Next point to walk to when heading from `from` to `to`, following the
+/// corridors down the stairwell instead of cutting through the floors
+pub fn stairwell_waypoint(from: Vec3, to: Vec3) -> Vec3 {
+    let floor = FloorPoints::floor_at(from.y);
+    let target_floor = FloorPoints::floor_at(to.y);
+
+    let points = FloorPoints::new(floor);
+    let (exit, next) = match target_floor.cmp(&floor) {
+        Ordering::Equal => return to,
+        // The end of a floor leads down to the start of the next one
+        Ordering::Greater => (points.end, FloorPoints::new(floor + 1).start),
+        Ordering::Less if floor > 1 => (points.start, FloorPoints::new(floor - 1).end),
+        Ordering::Less => return to,
+    };
+
+    if from.xz().distance(exit.xz()) < WAYPOINT_RADIUS {
+        next
+    } else {
+        exit
     }
 }
 
+/// Whether a world position falls inside the camera's view frustum
+pub fn is_in_view(camera: &Camera, camera_transform: &GlobalTransform, point: Vec3) -> bool {
+    camera
+        .world_to_ndc(camera_transform, point)
+        .is_some_and(|ndc| ndc.z > 0.0 && ndc.x.abs() <= 1.0 && ndc.y.abs() <= 1.0)
+}
+
+#[allow(clippy::type_complexity)]
 pub fn enemies_animation(
-    mut animation_players: Query<
-        (
-            &mut AnimationPlayer,
-            &mut AnimationTransitions,
-            &LinearVelocity,
-            &EnemyAnimations,
-        ),
-        With<Enemy>,
-    >,
+    mut e_query: Query<(
+        &mut EnemyAnimations,
+        &EnemyAi,
+        &Enemy,
+        &Transform,
+        &LinearVelocity,
+    )>,
+    p_query: Query<&Transform, (With<Player>, Without<Enemy>)>,
+    mut a_query: Query<(&mut AnimationPlayer, &mut AnimationTransitions)>,
 ) {
-    for (mut player, mut transitions, linear_velocity, animations) in &mut animation_players {
-        let current_animation = if linear_velocity.length() > 0.0 {
-            animations.animations[1]
-        } else {
-            animations.animations[0]
+    let Ok(p_transform) = p_query.get_single() else {
+        return;
+    };
+
+    for (mut animations, ai, enemy, e_transform, linear_velocity) in &mut e_query {
+        let Some(Ok((mut player, mut transitions))) =
+            animations.player.map(|entity| a_query.get_mut(entity))
+        else {
+            continue;
+        };
+
+        let distance = p_transform.translation.distance(e_transform.translation);
+        let wanted = match ai.state {
+            _ if enemy.speed <= 0.0 => EnemyAnimation::Idle,
+            AiState::Idle | AiState::Vanish => EnemyAnimation::Idle,
+            AiState::Stalk => EnemyAnimation::Crawl,
+            AiState::Chase if distance < ATTACK_DISTANCE => EnemyAnimation::Attack,
+            AiState::Chase | AiState::Flee => EnemyAnimation::Run,
+        };
+        let Some((name, node)) = animations.node(wanted) else {
+            continue;
         };
 
-        if !player.is_playing_animation(current_animation) {
+        if animations.current != Some(name) {
             transitions
-                .play(&mut player, current_animation, Duration::ZERO)
+                .play(&mut player, node.index, animations.crossfade)
                 .repeat();
+            animations.current = Some(name);
+        }
+
+        // Keeps the feet from sliding, and freezes a statue mid-stride
+        if node.speed > 0.0 {
+            if let Some(animation) = player.animation_mut(node.index) {
+                animation.set_speed(linear_velocity.xz().length() / node.speed);
+            }
+        }
+    }
+}
+
+/// Plays an enemy's chase cue when it starts chasing the player
+pub fn enemies_cues(
+    mut commands: Commands,
+    audio_assets: Res<AudioAssets>,
+    mut rng: ResMut<GlobalEntropy<WyRand>>,
+    query: Query<(&EnemyAi, &EnemyCues, &Transform)>,
+) {
+    for (ai, cues, transform) in &query {
+        if !ai.entered || ai.state != AiState::Chase {
+            continue;
+        }
+
+        let Some(sound) = cues.chase else {
+            continue;
+        };
+
+        if let Some(source) = sound.source(&audio_assets, &mut *rng) {
+            play_sound_at(
+                &mut commands,
+                sound.category(),
+                source,
+                transform.translation,
+            );
         }
     }
 }
+
+pub fn enemies_despawn(mut commands: Commands) {
+    commands.insert_resource(EnemyRegistry::default());
+}
+
+pub fn enemies_lifetime(
+    time: Res<Time>,
+    mut commands: Commands,
+    mut registry: ResMut<EnemyRegistry>,
+) {
+    registry.despawn_where(&mut commands, |record| {
+        record
+            .lifetime
+            .as_mut()
+            .is_some_and(|timer| timer.tick(time.delta()).finished())
+    });
+}
+
+/// Despawns enemies whose floor is no longer loaded around the player
+pub fn enemies_cull(
+    mut commands: Commands,
+    pool: Res<ObjectPool>,
+    mut registry: ResMut<EnemyRegistry>,
+) {
+    registry.despawn_where(&mut commands, |record| {
+        let culled = !pool.active_rooms.contains_key(&record.floor);
+        if culled {
+            debug!(
+                "Culling the {:?} enemy of floor {}",
+                record.action, record.floor
+            );
+        }
+        culled
+    });
+}
@@ -0,0 +1,188 @@
+use super::{
+    archetypes::EnemyArchetypes,
+    components::{AiState, EnemyAi},
+    resources::EnemyModels,
+    systems::{enemies_ai, is_in_view, load_enemy_models},
+};
+use crate::{
+    game::{
+        player::components::{Player, PlayerCamera},
+        spawn_enemy,
+        testing::{run_for, test_app},
+    },
+    resources::MapAssets,
+};
+use avian3d::prelude::*;
+use bevy::{
+    ecs::system::RunSystemOnce,
+    gltf::Gltf,
+    prelude::*,
+    render::{
+        camera::{camera_system, ManualTextureViews, RenderTarget},
+        render_asset::RenderAssetUsages,
+        render_resource::{Extent3d, TextureDimension, TextureFormat},
+    },
+    window::{WindowCreated, WindowResized, WindowScaleFactorChanged},
+};
+
+/// Test app with a player at the origin and their camera looking down -Z.
+/// The camera renders to an image, so its projection is computed without a window.
+fn app_with_camera() -> (App, Entity) {
+    let mut app = test_app();
+    app.add_event::<WindowResized>()
+        .add_event::<WindowCreated>()
+        .add_event::<WindowScaleFactorChanged>()
+        .init_resource::<ManualTextureViews>()
+        .insert_resource(SpatialQueryPipeline::default())
+        .add_systems(Update, (camera_system::<Projection>, enemies_ai).chain());
+
+    let image = app
+        .world_mut()
+        .resource_mut::<Assets<Image>>()
+        .add(Image::new_fill(
+            Extent3d {
+                width: 1280,
+                height: 720,
+                ..default()
+            },
+            TextureDimension::D2,
+            &[0, 0, 0, 255],
+            TextureFormat::Rgba8UnormSrgb,
+            RenderAssetUsages::default(),
+        ));
+
+    let world = app.world_mut();
+    world.spawn((Player::default(), Transform::default()));
+    let camera = world
+        .spawn((
+            Camera {
+                target: RenderTarget::Image(image),
+                ..default()
+            },
+            // Same as the player's camera, the far plane only limits culling
+            Projection::Perspective(PerspectiveProjection {
+                far: 1.0,
+                ..default()
+            }),
+            PlayerCamera::default(),
+            Transform::default(),
+            GlobalTransform::default(),
+        ))
+        .id();
+
+    app.update();
+    (app, camera)
+}
+
+fn turn_camera(app: &mut App, camera: Entity, yaw: f32) {
+    let transform = Transform::from_rotation(Quat::from_rotation_y(yaw));
+    app.world_mut()
+        .entity_mut(camera)
+        .insert((transform, GlobalTransform::from(transform)));
+}
+
+fn in_view(app: &mut App, point: Vec3) -> bool {
+    let world = app.world_mut();
+    let (camera, transform) = world.query::<(&Camera, &GlobalTransform)>().single(world);
+    is_in_view(camera, transform, point)
+}
+
+fn spawn_statue(app: &mut App, position: Vec3) -> Entity {
+    app.world_mut().run_system_once(
+        move |mut commands: Commands,
+              map_assets: Res<MapAssets>,
+              archetypes: Res<Assets<EnemyArchetypes>>,
+              asset_server: Res<AssetServer>,
+              mut graphs: ResMut<Assets<AnimationGraph>>| {
+            let archetype = archetypes
+                .get(&map_assets.enemy_archetypes)
+                .and_then(|archetypes| archetypes.get("statue"))
+                .unwrap();
+            let statue = spawn_enemy(
+                archetype,
+                &mut commands,
+                &asset_server,
+                &mut graphs,
+                position,
+                None,
+            );
+            // The physics plugins would add the velocity and sync the transform
+            commands.entity(statue).insert((
+                Transform::from_translation(position),
+                LinearVelocity::default(),
+            ));
+            statue
+        },
+    )
+}
+
+fn velocity(app: &App, enemy: Entity) -> Vec3 {
+    app.world().get::<LinearVelocity>(enemy).unwrap().0
+}
+
+#[test]
+fn in_view_only_in_front_of_the_camera() {
+    let (mut app, camera) = app_with_camera();
+
+    assert!(in_view(&mut app, Vec3::new(0.0, 0.0, -5.0)));
+    // Well past the far plane, which only limits culling
+    assert!(in_view(&mut app, Vec3::new(0.0, 0.0, -50.0)));
+    assert!(in_view(&mut app, Vec3::new(1.0, -0.5, -3.0)));
+
+    assert!(!in_view(&mut app, Vec3::new(0.0, 0.0, 5.0)));
+    assert!(!in_view(&mut app, Vec3::new(10.0, 0.0, -1.0)));
+    assert!(!in_view(&mut app, Vec3::new(0.0, 10.0, -1.0)));
+
+    turn_camera(&mut app, camera, std::f32::consts::PI);
+    assert!(!in_view(&mut app, Vec3::new(0.0, 0.0, -5.0)));
+    assert!(in_view(&mut app, Vec3::new(0.0, 0.0, 5.0)));
+}
+
+#[test]
+fn statue_is_frozen_while_in_view() {
+    let (mut app, _) = app_with_camera();
+    let statue = spawn_statue(&mut app, Vec3::new(0.0, 0.0, -5.0));
+
+    run_for(&mut app, 1.0);
+
+    let ai = app.world().get::<EnemyAi>(statue).unwrap();
+    assert_eq!(ai.state, AiState::Chase);
+    assert_eq!(velocity(&app, statue), Vec3::ZERO);
+}
+
+#[test]
+fn statue_moves_once_out_of_view() {
+    let (mut app, camera) = app_with_camera();
+    let statue = spawn_statue(&mut app, Vec3::new(0.0, 0.0, -5.0));
+    run_for(&mut app, 0.5);
+    assert_eq!(velocity(&app, statue), Vec3::ZERO);
+
+    // Looks away, the statue closes in on the player
+    turn_camera(&mut app, camera, std::f32::consts::PI);
+    run_for(&mut app, 0.5);
+    let moving = velocity(&app, statue);
+    assert!((moving.length() - 1.5).abs() < 1e-3, "{moving}");
+    assert!(moving.normalize().dot(Vec3::Z) > 0.99, "{moving}");
+
+    // Looks back, it stops dead
+    turn_camera(&mut app, camera, 0.0);
+    app.update();
+    assert_eq!(velocity(&app, statue), Vec3::ZERO);
+}
+
+#[test]
+fn models_of_the_archetypes_are_preloaded_once() {
+    let mut app = test_app();
+    app.init_asset::<Gltf>()
+        .init_resource::<EnemyModels>()
+        .add_systems(Update, load_enemy_models);
+    app.update();
+
+    // The statue shares the mental model
+    let models = &app.world().resource::<EnemyModels>().models;
+    assert_eq!(models.len(), 1);
+    assert_eq!(
+        models[0].path().map(|path| path.to_string()),
+        Some("map/mental.gltf".to_string())
+    );
+}
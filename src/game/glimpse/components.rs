@@ -1,4 +1,7 @@
 use bevy::prelude::*;
 
 #[derive(Component)]
-pub struct Glimpse;
+pub struct Glimpse {
+    /// Floor the glimpse currently stands on, pooled glimpses are reused across floors
+    pub floor: usize,
+}
@@ -1,14 +1,22 @@
 use crate::AppState;
 use bevy::prelude::*;
-use systems::update_glimpses;
+use resources::GlimpsePool;
+use systems::*;
 
 pub mod components;
+pub mod resources;
 mod systems;
 
 pub struct GlimpsePlugin;
 
 impl Plugin for GlimpsePlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Update, update_glimpses.run_if(in_state(AppState::Game)));
+        app.init_resource::<GlimpsePool>()
+            .add_systems(OnExit(AppState::Game), glimpses_despawn)
+            .add_systems(
+                Update,
+                (glimpses_cull, update_glimpses.after(glimpses_cull))
+                    .run_if(in_state(AppState::Game)),
+            );
     }
 }
@@ -0,0 +1,51 @@
+use bevy::{prelude::*, utils::HashMap};
+
+/// Where a floor's glimpse stands and which of the glimpse textures it shows
+#[derive(Clone, Copy, Debug)]
+pub struct GlimpseSpot {
+    pub position: Vec3,
+    pub texture: usize,
+}
+
+/// Glimpses of the floors around the player, spawned as their rooms are and
+/// reused once those rooms are released
+#[derive(Resource, Default)]
+pub struct GlimpsePool {
+    /// Rolled once per floor, `None` if it has no glimpse or it was already seen
+    spots: HashMap<usize, Option<GlimpseSpot>>,
+    available: Vec<Entity>,
+    pub active: HashMap<usize, Entity>,
+}
+
+impl GlimpsePool {
+    /// The glimpse of a floor, rolling it the first time the floor is asked for
+    pub fn spot(
+        &mut self,
+        floor: usize,
+        roll: impl FnOnce() -> Option<GlimpseSpot>,
+    ) -> Option<GlimpseSpot> {
+        *self.spots.entry(floor).or_insert_with(roll)
+    }
+
+    /// Takes a hidden glimpse to reuse, if there is one
+    pub fn take(&mut self) -> Option<Entity> {
+        self.available.pop()
+    }
+
+    pub fn insert(&mut self, floor: usize, entity: Entity) {
+        self.active.insert(floor, entity);
+    }
+
+    /// Returns the floor's glimpse to the pool, it should be hidden by the caller
+    pub fn release(&mut self, floor: usize) -> Option<Entity> {
+        let entity = self.active.remove(&floor)?;
+        self.available.push(entity);
+        Some(entity)
+    }
+
+    /// Releases the floor's glimpse for good, it won't show up there again
+    pub fn consume(&mut self, floor: usize) -> Option<Entity> {
+        self.spots.insert(floor, None);
+        self.release(floor)
+    }
+}
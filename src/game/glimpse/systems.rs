@@ -1,31 +1,156 @@
-use super::components::Glimpse;
-use crate::{game::player::components::Player, resources::AudioAssets};
+use super::{
+    components::Glimpse,
+    resources::{GlimpsePool, GlimpseSpot},
+};
+use crate::{
+    game::{
+        audio::{components::SoundCategory, systems::play_sound_at},
+        map::resources::{FloorAction, FloorPoints, Map, ObjectPool},
+        player::components::{Player, PlayerCamera},
+    },
+    resources::{AudioAssets, MapAssets},
+    AppState,
+};
 use bevy::prelude::*;
+use bevy_mod_billboard::prelude::*;
+use bevy_rand::prelude::*;
+use rand::prelude::*;
 
-#[allow(clippy::type_complexity)]
+/// Distance at which a glimpse vanishes no matter where the player looks
+const TRIGGER_DISTANCE: f32 = 2.3;
+/// Distance at which a glimpse vanishes once the player looks at it
+const LOOK_DISTANCE: f32 = 6.0;
+/// Cosine of the widest angle between the view direction and a glimpse that
+/// still counts as looking at it
+const LOOK_ANGLE_COS: f32 = 0.97;
+
+/// Spawns the glimpses of the rooms the `ObjectPool` has active, and hides
+/// the ones whose rooms were released
+pub fn glimpses_cull(
+    mut commands: Commands,
+    map: Res<Map>,
+    map_assets: Res<MapAssets>,
+    rooms: Res<ObjectPool>,
+    mut pool: ResMut<GlimpsePool>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut rng: ResMut<GlobalEntropy<WyRand>>,
+) {
+    let released = pool
+        .active
+        .keys()
+        .filter(|floor| !rooms.active_rooms.contains_key(*floor))
+        .copied()
+        .collect::<Vec<_>>();
+    for floor in released {
+        if let Some(entity) = pool.release(floor) {
+            commands.entity(entity).insert(Visibility::Hidden);
+        }
+    }
+
+    for &floor in rooms.active_rooms.keys() {
+        if pool.active.contains_key(&floor) {
+            continue;
+        }
+
+        let textures = map_assets.glimpse_textures.len();
+        let Some(spot) = pool.spot(floor, || roll_spot(&map, floor, textures, &mut *rng)) else {
+            continue;
+        };
+
+        let transform = Transform::from_translation(spot.position);
+        let texture = BillboardTextureHandle(map_assets.glimpse_textures[spot.texture].clone());
+        let glimpse = Glimpse { floor };
+
+        let entity = match pool.take() {
+            Some(entity) => {
+                commands.entity(entity).insert((
+                    transform,
+                    texture,
+                    glimpse,
+                    Visibility::Inherited,
+                ));
+                entity
+            }
+            None => commands
+                .spawn((
+                    BillboardTextureBundle {
+                        transform,
+                        texture,
+                        mesh: BillboardMeshHandle(meshes.add(Rectangle::new(0.6, 0.6))),
+                        ..default()
+                    },
+                    glimpse,
+                    StateScoped(AppState::Game),
+                ))
+                .id(),
+        };
+        pool.insert(floor, entity);
+    }
+}
+
+/// About one in six plain floors gets a glimpse somewhere along its corridor
+fn roll_spot(map: &Map, floor: usize, textures: usize, rng: &mut impl Rng) -> Option<GlimpseSpot> {
+    if map.floors.get(floor)?.action != FloorAction::Steps || rng.gen_range(1..7) != 1 {
+        return None;
+    }
+
+    let floor_y = FloorPoints::new(floor).mid.y;
+    let floor_z = if floor.is_multiple_of(2) {
+        6.55 // Even index
+    } else {
+        0.3 // Odd index
+    };
+
+    Some(GlimpseSpot {
+        position: Vec3::new(rng.gen_range(0.8..7.2), floor_y, floor_z),
+        texture: rng.gen_range(0..textures),
+    })
+}
+
+/// Makes the glimpse of the player's floor vanish once they get close or look at it
 pub fn update_glimpses(
     mut commands: Commands,
     audio_assets: Res<AudioAssets>,
-    g_query: Query<(&Transform, Entity), (With<Glimpse>, Without<Player>)>,
-    p_query: Query<(&Player, &Transform), Without<Glimpse>>,
+    mut pool: ResMut<GlimpsePool>,
+    g_query: Query<(&Glimpse, &Transform)>,
+    p_query: Query<(&Player, &Transform)>,
+    c_query: Query<&GlobalTransform, With<PlayerCamera>>,
 ) {
-    for (player, p_transform) in &p_query {
-        for (g_transform, g_entity) in &g_query {
-            if player.floor_index - 1 == ((-g_transform.translation.y - 0.5) / 2.0) as usize
-                && p_transform.translation.distance(Vec3::new(
-                    g_transform.translation.x,
-                    g_transform.translation.y,
-                    g_transform.translation.z,
-                )) < 2.3
-            {
-                // TODO: Make a 3d audio
-                commands.spawn(AudioBundle {
-                    source: audio_assets.no_sfx.clone(),
-                    settings: PlaybackSettings::DESPAWN,
-                });
-
-                commands.entity(g_entity).despawn();
-            }
-        }
+    let (Ok((player, p_transform)), Ok(c_transform)) = (p_query.get_single(), c_query.get_single())
+    else {
+        return;
+    };
+
+    let Some((glimpse, g_transform)) = pool
+        .active
+        .get(&player.floor_index)
+        .and_then(|&entity| g_query.get(entity).ok())
+    else {
+        return;
+    };
+
+    let position = g_transform.translation;
+    let to_glimpse = position - c_transform.translation();
+    let looked_at = to_glimpse.length() < LOOK_DISTANCE
+        && c_transform.forward().dot(to_glimpse.normalize_or_zero()) > LOOK_ANGLE_COS;
+
+    if !looked_at && p_transform.translation.distance(position) >= TRIGGER_DISTANCE {
+        return;
     }
+
+    play_sound_at(
+        &mut commands,
+        SoundCategory::Scare,
+        audio_assets.no_sfx.clone(),
+        position,
+    );
+
+    if let Some(entity) = pool.consume(glimpse.floor) {
+        commands.entity(entity).insert(Visibility::Hidden);
+    }
+}
+
+pub fn glimpses_despawn(mut commands: Commands) {
+    // The next run rolls the glimpses of its floors anew
+    commands.insert_resource(GlimpsePool::default());
 }
@@ -0,0 +1,16 @@
+use crate::AppState;
+use bevy::prelude::*;
+use systems::*;
+
+pub mod resources;
+pub mod systems;
+
+pub struct LightingPlugin;
+
+impl Plugin for LightingPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(AppState::Game), spawn_lighting)
+            .add_systems(OnExit(AppState::Game), despawn_lighting)
+            .add_systems(Update, update_lighting.run_if(in_state(AppState::Game)));
+    }
+}
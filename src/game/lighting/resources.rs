@@ -0,0 +1,136 @@
+use bevy::prelude::*;
+use serde::Deserialize;
+use std::{collections::VecDeque, time::Duration};
+
+/// Ambient light and fog as seen through the player camera
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct LightingState {
+    pub brightness: f32,
+    pub color: Color,
+    /// Distance at which the fog becomes fully opaque
+    pub fog_distance: f32,
+}
+
+impl LightingState {
+    fn lerp(&self, target: &Self, t: f32) -> Self {
+        Self {
+            brightness: self.brightness.lerp(target.brightness, t),
+            color: LinearRgba::from(self.color)
+                .mix(&LinearRgba::from(target.color), t)
+                .into(),
+            fog_distance: self.fog_distance.lerp(target.fog_distance, t),
+        }
+    }
+}
+
+/// Eases the lighting towards new values over `duration` seconds,
+/// values that are left out stay as they are
+#[derive(Deserialize, Clone, Copy, Default, Debug)]
+#[serde(default)]
+pub struct LightingTransition {
+    pub brightness: Option<f32>,
+    /// sRGB components
+    pub color: Option<[f32; 3]>,
+    pub fog_distance: Option<f32>,
+    pub duration: f32,
+    /// Undone once the player leaves the floor, otherwise the new values
+    /// become the base lighting
+    pub floor_scoped: bool,
+}
+
+impl LightingTransition {
+    fn target(&self, from: &LightingState) -> LightingState {
+        LightingState {
+            brightness: self.brightness.unwrap_or(from.brightness),
+            color: self.color.map_or(from.color, |[red, green, blue]| {
+                Color::srgb(red, green, blue)
+            }),
+            fog_distance: self.fog_distance.unwrap_or(from.fog_distance),
+        }
+    }
+}
+
+struct ActiveTransition {
+    from: LightingState,
+    to: LightingState,
+    timer: Timer,
+}
+
+/// Owns the ambient light and the camera fog, events queue transitions on it
+#[derive(Resource)]
+pub struct Lighting {
+    /// Lighting the floors go back to once the player leaves them
+    pub base: LightingState,
+    pub current: LightingState,
+    queue: VecDeque<LightingTransition>,
+    active: Option<ActiveTransition>,
+    /// Whether a floor scoped transition has been started since the last restore
+    floor_scoped: bool,
+}
+
+impl Lighting {
+    pub fn new(base: LightingState) -> Self {
+        Self {
+            base,
+            current: base,
+            queue: VecDeque::new(),
+            active: None,
+            floor_scoped: false,
+        }
+    }
+
+    /// Whether the lighting has floor scoped changes that leaving the floor should undo
+    pub fn is_floor_scoped(&self) -> bool {
+        self.floor_scoped || self.queue.iter().any(|transition| transition.floor_scoped)
+    }
+
+    /// Starts the transition once the ones before it have finished
+    pub fn push(&mut self, transition: LightingTransition) {
+        self.queue.push_back(transition);
+    }
+
+    /// Drops the pending transitions and starts this one right away
+    pub fn replace(&mut self, transition: LightingTransition) {
+        self.queue.clear();
+        self.start(transition.target(&self.current), transition.duration);
+    }
+
+    /// Drops the pending transitions and eases back to the base lighting
+    pub fn restore(&mut self, duration: f32) {
+        self.queue.clear();
+        self.floor_scoped = false;
+        self.start(self.base, duration);
+    }
+
+    pub fn update(&mut self, delta: Duration) {
+        if self.active.is_none() {
+            if let Some(transition) = self.queue.pop_front() {
+                if transition.floor_scoped {
+                    self.floor_scoped = true;
+                } else {
+                    self.base = transition.target(&self.base);
+                }
+                self.start(transition.target(&self.current), transition.duration);
+            }
+        }
+
+        let Some(active) = &mut self.active else {
+            return;
+        };
+
+        active.timer.tick(delta);
+        self.current = active.from.lerp(&active.to, active.timer.fraction());
+
+        if active.timer.finished() {
+            self.active = None;
+        }
+    }
+
+    fn start(&mut self, to: LightingState, duration: f32) {
+        self.active = Some(ActiveTransition {
+            from: self.current,
+            to,
+            timer: Timer::from_seconds(duration, TimerMode::Once),
+        });
+    }
+}
@@ -0,0 +1,33 @@
+use super::resources::{Lighting, LightingState};
+use crate::{game::player::components::PlayerCamera, settings::Settings};
+use bevy::prelude::*;
+
+pub fn spawn_lighting(mut commands: Commands, settings: Res<Settings>) {
+    commands.insert_resource(Lighting::new(LightingState {
+        brightness: 80.0,
+        color: Color::WHITE,
+        fog_distance: settings.fog_distance,
+    }));
+}
+
+pub fn despawn_lighting(mut commands: Commands) {
+    commands.remove_resource::<Lighting>();
+}
+
+pub fn update_lighting(
+    time: Res<Time>,
+    mut lighting: ResMut<Lighting>,
+    mut ambient_light: ResMut<AmbientLight>,
+    mut query: Query<&mut FogSettings, With<PlayerCamera>>,
+) {
+    lighting.update(time.delta());
+
+    ambient_light.brightness = lighting.current.brightness;
+    ambient_light.color = lighting.current.color;
+
+    for mut fog in &mut query {
+        if let FogFalloff::Linear { end, .. } = &mut fog.falloff {
+            *end = lighting.current.fog_distance;
+        }
+    }
+}
@@ -5,3 +5,11 @@ pub struct FloorLabelUi;
 
 #[derive(Component)]
 pub struct FloorLabel;
+
+/// Despawned once the player leaves the floor with this index
+#[derive(Component)]
+pub struct FloorScoped(pub usize);
+
+/// Blocks the stairwell until a floor event opens it
+#[derive(Component)]
+pub struct Door;
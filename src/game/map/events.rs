@@ -0,0 +1,173 @@
+use super::resources::{FloorAction, FloorPoints};
+use crate::{
+    game::{audio::components::SoundCategory, lighting::resources::LightingTransition},
+    resources::AudioAssets,
+};
+use bevy::prelude::*;
+use rand::prelude::*;
+use serde::Deserialize;
+use std::collections::HashMap;
+
+/// Scripted floor events, loaded from `map/events.floors.ron`
+#[derive(Asset, TypePath, Deserialize, Debug)]
+pub struct FloorEvents {
+    pub events: HashMap<FloorAction, Vec<FloorStage>>,
+}
+
+impl FloorEvents {
+    pub fn stage(&self, action: FloorAction, stage: usize) -> Option<&FloorStage> {
+        self.events.get(&action)?.get(stage)
+    }
+}
+
+/// One step of a floor event: once `trigger` fires, `effects` are applied
+/// and the floor moves on to the `next` stage, or ends if there is none
+#[derive(Deserialize, Clone, Debug)]
+pub struct FloorStage {
+    pub trigger: FloorTrigger,
+    #[serde(default)]
+    pub effects: Vec<FloorEffect>,
+    #[serde(default)]
+    pub next: Option<usize>,
+}
+
+#[derive(Deserialize, Clone, Copy, Debug)]
+pub enum FloorTrigger {
+    /// Fires as soon as the stage is entered
+    Immediately,
+    /// Fires when the player gets closer than `radius` to a point of the floor
+    Near {
+        point: FloorPoint,
+        #[serde(default)]
+        offset: Vec3,
+        radius: f32,
+    },
+    /// Fires once the stage has been running for the given amount of seconds
+    Elapsed(f32),
+}
+
+#[derive(Deserialize, Clone, Copy, Debug)]
+pub enum FloorPoint {
+    Start,
+    Mid,
+    End,
+}
+
+impl FloorPoint {
+    /// World position of this point, `offset` is given for even floors and
+    /// mirrored on odd ones
+    pub fn resolve(&self, points: &FloorPoints, offset: Vec3) -> Vec3 {
+        let point = match self {
+            Self::Start => points.start,
+            Self::Mid => points.mid,
+            Self::End => points.end,
+        };
+
+        point + points.mirrored(offset)
+    }
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub enum FloorEffect {
+    /// Spawns an enemy of an archetype from `map/enemies.archetypes.ron`
+    SpawnEnemy {
+        archetype: String,
+        point: FloorPoint,
+        #[serde(default)]
+        offset: Vec3,
+        /// Overrides the archetype's speed
+        #[serde(default)]
+        speed: Option<f32>,
+        /// Seconds until the enemy despawns on its own
+        #[serde(default)]
+        lifetime: Option<f32>,
+    },
+    /// Despawns the enemies spawned by this floor
+    DespawnEnemy,
+    SpawnWall {
+        point: FloorPoint,
+        #[serde(default)]
+        offset: Vec3,
+    },
+    /// Spawns a locked door that stays shut until `OpenDoors`
+    SpawnDoor {
+        point: FloorPoint,
+        #[serde(default)]
+        offset: Vec3,
+    },
+    /// Opens every door on the floor
+    OpenDoors,
+    /// Plays a sound that isn't placed in the world, like the radio
+    PlaySound(FloorSound),
+    /// Plays a sound from a point of the floor
+    PlaySoundAt {
+        sound: FloorSound,
+        point: FloorPoint,
+        #[serde(default)]
+        offset: Vec3,
+    },
+    /// Plays a sound `distance` metres behind the player's back
+    PlaySoundBehind {
+        sound: FloorSound,
+        distance: f32,
+    },
+    Lighting(LightingTransition),
+    /// Eases the lighting back to normal over the given amount of seconds
+    RestoreLighting(f32),
+    /// Blows out the player's lighter
+    ExtinguishLighter,
+    /// Smothers the player's lighter over the given amount of seconds
+    StarveLighter(f32),
+    /// Shakes the camera for the given amount of seconds
+    ShakeCamera(f32),
+}
+
+#[derive(Deserialize, Clone, Copy, Debug)]
+pub enum FloorSound {
+    Radio(usize),
+    Horror(usize),
+    RandomHorror,
+    FireOff,
+    Roar,
+    Stone,
+    Step,
+    LoudStep,
+    Breath,
+}
+
+impl FloorSound {
+    pub fn category(&self) -> SoundCategory {
+        match self {
+            Self::Radio(_) => SoundCategory::Radio,
+            Self::Horror(_) | Self::RandomHorror | Self::Roar | Self::Breath => {
+                SoundCategory::Scare
+            }
+            // Not Footsteps, which only has voices for the player's own steps
+            Self::Step | Self::LoudStep | Self::FireOff | Self::Stone => SoundCategory::Effect,
+        }
+    }
+
+    /// The sound to play, `None` if a script picks a radio or horror sound that doesn't exist
+    pub fn source(
+        &self,
+        audio_assets: &AudioAssets,
+        rng: &mut impl Rng,
+    ) -> Option<Handle<AudioSource>> {
+        let source = match self {
+            Self::Radio(i) => audio_assets.radio_sfx.get(*i),
+            Self::Horror(i) => audio_assets.horror_sfx.get(*i),
+            Self::RandomHorror => audio_assets.horror_sfx.get(rng.gen_range(0..2)),
+            Self::FireOff => Some(&audio_assets.fire_off),
+            Self::Roar => Some(&audio_assets.roar_sfx),
+            Self::Stone => Some(&audio_assets.stone_sfx),
+            Self::Step => Some(&audio_assets.step_sound),
+            Self::LoudStep => Some(&audio_assets.loud_step_sound),
+            Self::Breath => Some(&audio_assets.breath_sfx),
+        };
+
+        if source.is_none() {
+            warn!("Unknown sound {self:?}");
+        }
+        source.cloned()
+    }
+}
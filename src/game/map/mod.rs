@@ -1,11 +1,16 @@
 use crate::AppState;
 use bevy::prelude::*;
-use resources::ObjectPool;
+use bevy_common_assets::ron::RonAssetPlugin;
+use events::FloorEvents;
+use resources::{MapSeed, ObjectPool};
 use systems::*;
 
 pub mod components;
+pub mod events;
 pub mod resources;
 pub mod systems;
+#[cfg(test)]
+mod tests;
 
 pub const FLOOR_AMOUNT: usize = 210;
 
@@ -13,8 +18,13 @@ pub struct MapPlugin;
 
 impl Plugin for MapPlugin {
     fn build(&self, app: &mut App) {
-        app.insert_resource(ObjectPool::default())
-            .add_systems(OnEnter(AppState::Game), spawn_map)
-            .add_systems(Update, update_floors.run_if(in_state(AppState::Game)));
+        app.add_plugins(RonAssetPlugin::<FloorEvents>::new(&["floors.ron"]))
+            .insert_resource(ObjectPool::default())
+            .insert_resource(MapSeed::from_args().unwrap_or_default())
+            .add_systems(OnExit(AppState::Game), despawn_map)
+            .add_systems(
+                Update,
+                (update_floors, leave_floor).run_if(in_state(AppState::Game)),
+            );
     }
 }
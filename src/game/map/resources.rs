@@ -1,9 +1,40 @@
 use super::FLOOR_AMOUNT;
-use crate::resources::MapAssets;
+use crate::{resources::MapAssets, AppState};
 use avian3d::prelude::*;
 use bevy::{prelude::*, utils::HashMap};
-use bevy_rand::prelude::*;
 use rand::prelude::*;
+use serde::Deserialize;
+
+/// Seed used for every random choice made while generating the [`Map`]
+#[derive(Resource, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MapSeed(pub u64);
+
+impl MapSeed {
+    /// Reads the seed from a `--seed <number>` argument
+    pub fn from_args() -> Option<Self> {
+        let mut args = std::env::args().skip(1);
+
+        while let Some(arg) = args.next() {
+            let value = match arg.strip_prefix("--seed=") {
+                Some(value) => Some(value.to_string()),
+                None if arg == "--seed" => args.next(),
+                None => None,
+            };
+
+            if let Some(seed) = value.and_then(|value| value.parse().ok()) {
+                return Some(Self(seed));
+            }
+        }
+
+        None
+    }
+}
+
+impl Default for MapSeed {
+    fn default() -> Self {
+        Self(rand::random())
+    }
+}
 
 #[derive(Resource, Default)]
 pub struct ObjectPool {
@@ -56,6 +87,7 @@ impl ObjectPool {
                     },
                     ColliderConstructorHierarchy::new(Some(ColliderConstructor::TrimeshFromMesh)),
                     RigidBody::Static,
+                    StateScoped(AppState::Game),
                 ))
                 .id();
             new_entity
@@ -76,7 +108,7 @@ impl ObjectPool {
     }
 }
 
-#[derive(PartialEq, Eq, Default, Clone, Copy, Debug)]
+#[derive(PartialEq, Eq, Default, Clone, Copy, Debug, Hash, Deserialize)]
 pub enum FloorAction {
     #[default]
     Steps,
@@ -101,7 +133,54 @@ pub enum FloorAction {
 #[derive(Default, Clone, Debug)]
 pub struct Floor {
     pub action: FloorAction,
-    pub timer: f32,
+    /// Current stage of the floor event, `None` once it is over
+    pub stage: Option<usize>,
+    /// Seconds spent in the current stage
+    pub elapsed: f32,
+}
+
+/// Key points of a floor's corridor, used to place and trigger floor events
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FloorPoints {
+    pub even: bool,
+    pub start: Vec3,
+    pub mid: Vec3,
+    pub end: Vec3,
+}
+
+impl FloorPoints {
+    pub fn new(floor_index: usize) -> Self {
+        let floor_x = 4.0;
+        let floor_y = -1.0 - (floor_index as f32 - 1.0) * 2.0;
+        let even = floor_index.is_multiple_of(2);
+
+        let (floor_z, start_x, end_x) = if even {
+            (6.5, 7.5, 0.5)
+        } else {
+            (0.5, 0.5, 7.5)
+        };
+
+        Self {
+            even,
+            start: Vec3::new(start_x, floor_y, floor_z),
+            mid: Vec3::new(floor_x, floor_y, floor_z),
+            end: Vec3::new(end_x, floor_y, floor_z),
+        }
+    }
+
+    /// Index of the floor a body centred at height `y` stands on
+    pub fn floor_at(y: f32) -> usize {
+        ((-y - 0.5) as usize / 2) + 1
+    }
+
+    /// Mirrors an offset given for even floors so it also fits odd floors
+    pub fn mirrored(&self, offset: Vec3) -> Vec3 {
+        if self.even {
+            offset
+        } else {
+            Vec3::new(-offset.x, offset.y, -offset.z)
+        }
+    }
 }
 
 #[derive(PartialEq, Eq, Default, Clone, Copy, Debug, Hash)]
@@ -142,36 +221,39 @@ impl Default for Map {
 }
 
 impl Map {
-    pub fn generate(&mut self, rng: &mut ResMut<GlobalEntropy<WyRand>>) {
-        self.assign_floor_action(1, FloorAction::Proceed, 1.0);
+    pub fn generate(&mut self, rng: &mut impl Rng) {
+        self.assign_floor_action(1, FloorAction::Proceed);
 
         if rng.gen_bool(0.5) {
-            self.assign_random_floor_action(3..4, FloorAction::Radio2, 1.0);
+            self.assign_random_floor_action(rng, 3..4, FloorAction::Radio2);
         }
 
         if rng.gen_bool(2.0 / 3.0) {
-            self.assign_random_floor_action(5..6, FloorAction::Radio3, 1.0);
+            self.assign_random_floor_action(rng, 5..6, FloorAction::Radio3);
         }
 
-        self.assign_floor_action(7, FloorAction::Lock, 1.0);
+        self.assign_floor_action(7, FloorAction::Lock);
 
         if rng.gen_bool(0.5) {
-            self.assign_random_floor_action(8..9, FloorAction::Radio4, 1.0);
+            self.assign_random_floor_action(rng, 8..9, FloorAction::Radio4);
         }
 
-        self.assign_random_floor_action(10..11, FloorAction::Breath, 1.0);
-        self.assign_random_floor_action(12..13, FloorAction::Steps, 1.0);
-        self.assign_random_floor_action(10..19, FloorAction::Flash, 1.0);
-        self.assign_random_floor_action(20..22, FloorAction::Lights, 1.0);
+        self.assign_random_floor_action(rng, 10..11, FloorAction::Breath);
+        self.assign_random_floor_action(rng, 12..13, FloorAction::Steps);
+        self.assign_random_floor_action(rng, 10..19, FloorAction::Flash);
+        self.assign_random_floor_action(rng, 20..22, FloorAction::Lights);
 
         match rng.gen_range(0..4) {
-            1 => self.assign_random_floor_action(25..28, FloorAction::Trick1, 1.0),
-            2 => self.assign_random_floor_action(25..28, FloorAction::Trick2, 1.0),
+            1 => self.assign_random_floor_action(rng, 25..28, FloorAction::Trick1),
+            2 => self.assign_random_floor_action(rng, 25..28, FloorAction::Trick2),
             _ => {}
         }
 
-        self.assign_random_floor_action(29..33, FloorAction::Run, 1.0);
-        self.assign_random_floor_action(34..37, FloorAction::Scp173, 1.0);
+        self.assign_random_floor_action(rng, 29..33, FloorAction::Run);
+        self.assign_random_floor_action(rng, 34..37, FloorAction::Scp173);
+        self.assign_random_floor_action(rng, 38..45, FloorAction::Cell);
+        self.assign_random_floor_action(rng, 46..56, FloorAction::Trap);
+        self.assign_random_floor_action(rng, 57..69, FloorAction::Roar);
 
         for _ in 0..8 {
             let rand_action = match rng.gen_range(1..10) {
@@ -188,6 +270,7 @@ impl Map {
             loop {
                 let temp = rng.gen_range(25..69);
                 if self.floors[temp].action == FloorAction::Steps {
+                    // Only picks the room, the event itself stays dormant
                     self.floors[temp].action = rand_action;
                     break;
                 }
@@ -209,34 +292,36 @@ impl Map {
             loop {
                 let temp = rng.gen_range(75..200);
                 if self.floors[temp].action == FloorAction::Steps {
+                    // Only picks the room, the event itself stays dormant
                     self.floors[temp].action = rand_action;
                     break;
                 }
             }
         }
 
-        self.assign_random_floor_action(150..200, FloorAction::Darkness, 1.0);
+        self.assign_random_floor_action(rng, 150..200, FloorAction::Darkness);
         self.gen_rooms(rng);
     }
 
-    fn assign_floor_action(&mut self, index: usize, action: FloorAction, duration: f32) {
+    fn assign_floor_action(&mut self, index: usize, action: FloorAction) {
         if let Some(floor) = self.floors.get_mut(index) {
             floor.action = action;
-            floor.timer = duration;
+            floor.stage = Some(0);
+            floor.elapsed = 0.0;
         }
     }
 
     fn assign_random_floor_action(
         &mut self,
+        rng: &mut impl Rng,
         range: std::ops::Range<usize>,
         action: FloorAction,
-        duration: f32,
     ) {
-        let temp = rand::thread_rng().gen_range(range);
-        self.assign_floor_action(temp, action, duration);
+        let temp = rng.gen_range(range);
+        self.assign_floor_action(temp, action);
     }
 
-    fn gen_rooms(&mut self, rng: &mut ResMut<GlobalEntropy<WyRand>>) {
+    fn gen_rooms(&mut self, rng: &mut impl Rng) {
         for i in 0..self.floor_amount - 1 {
             let kind = if i == 0 {
                 RoomType::Map0
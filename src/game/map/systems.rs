@@ -1,450 +1,293 @@
-use super::resources::{FloorAction, Map};
+use super::{
+    components::{Door, FloorScoped},
+    events::{FloorEffect, FloorEvents, FloorTrigger},
+    resources::{FloorPoints, Map, ObjectPool},
+};
 use crate::{
     game::{
-        player::components::{Player, PlayerCamera},
+        audio::systems::{play_sound, play_sound_at},
+        enemy::{
+            archetypes::EnemyArchetypes,
+            resources::{EnemyRecord, EnemyRegistry},
+        },
+        lighting::resources::Lighting,
+        player::components::{CameraShake, Dying, Lighter, Player, PlayerCamera},
         spawn_enemy,
     },
     resources::{AudioAssets, MapAssets},
+    AppState,
 };
 use avian3d::prelude::*;
-use bevy::prelude::*;
+use bevy::{ecs::system::SystemParam, prelude::*};
 use bevy_rand::prelude::*;
-use rand::prelude::*;
 
-pub fn spawn_map(mut ambient_light: ResMut<AmbientLight>) {
-    ambient_light.brightness = 80.0;
+/// Assets the floor events spawn things with
+#[derive(SystemParam)]
+pub struct FloorAssets<'w> {
+    audio_assets: Res<'w, AudioAssets>,
+    map_assets: Res<'w, MapAssets>,
+    asset_server: Res<'w, AssetServer>,
+    archetypes: Res<'w, Assets<EnemyArchetypes>>,
+    graphs: ResMut<'w, Assets<AnimationGraph>>,
+    meshes: ResMut<'w, Assets<Mesh>>,
+    materials: ResMut<'w, Assets<StandardMaterial>>,
+}
+
+pub fn despawn_map(mut commands: Commands) {
+    // The pooled rooms are state scoped, so the pool only has to forget them
+    commands.insert_resource(ObjectPool::default());
+    commands.remove_resource::<Map>();
 }
 
-#[allow(clippy::too_many_arguments)]
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
 pub fn update_floors(
+    time: Res<Time>,
     mut map: ResMut<Map>,
     mut commands: Commands,
-    audio_assets: Res<AudioAssets>,
-    mut p_query: Query<(&Player, &Transform, &mut LinearVelocity), Without<PlayerCamera>>,
-    mut c_query: Query<&mut Transform, (With<PlayerCamera>, Without<Player>)>,
+    mut assets: FloorAssets,
+    floor_events: Res<Assets<FloorEvents>>,
+    p_query: Query<(&Player, &Transform), Without<PlayerCamera>>,
+    c_query: Query<(Entity, &GlobalTransform), (With<PlayerCamera>, Without<Player>)>,
+    d_query: Query<(Entity, &FloorScoped), With<Door>>,
     mut rng: ResMut<GlobalEntropy<WyRand>>,
-    mut ambient_light: ResMut<AmbientLight>,
-    mut cur_enemy: Local<Option<Entity>>,
-    map_assets: Res<MapAssets>,
-    mut graphs: ResMut<Assets<AnimationGraph>>,
-    mut meshes: ResMut<Assets<Mesh>>,
-    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut lighting: ResMut<Lighting>,
+    mut l_query: Query<&mut Lighter>,
+    mut enemies: ResMut<EnemyRegistry>,
 ) {
-    for (player, p_transform, mut linear_velocity) in &mut p_query {
-        if let Ok(mut c_transform) = c_query.get_single_mut() {
-            let player_floor = player.floor_index;
+    let Some(floor_events) = floor_events.get(&assets.map_assets.floor_events) else {
+        return;
+    };
 
-            if map.floors[player_floor].timer > 0.0 {
-                let floor_x = 4.0;
-                let floor_y = -1.0 - (player_floor as f32 - 1.0) * 2.0;
+    for (player, p_transform) in &p_query {
+        let player_floor = player.floor_index;
+        let floor = &mut map.floors[player_floor];
 
-                let (floor_z, start_x, end_x) =
-                    if (player_floor as f32 / 2.0).floor() == (player_floor as f32 / 2.0).ceil() {
-                        (6.5, 7.5, 0.5) // Even
-                    } else {
-                        (0.5, 0.5, 7.5) // Odd
+        let Some(stage) = floor
+            .stage
+            .and_then(|stage| floor_events.stage(floor.action, stage))
+        else {
+            continue;
+        };
+
+        let points = FloorPoints::new(player_floor);
+
+        let triggered = match stage.trigger {
+            FloorTrigger::Immediately => true,
+            FloorTrigger::Near {
+                point,
+                offset,
+                radius,
+            } => {
+                p_transform
+                    .translation
+                    .distance(point.resolve(&points, offset))
+                    < radius
+            }
+            FloorTrigger::Elapsed(secs) => floor.elapsed >= secs,
+        };
+
+        if !triggered {
+            floor.elapsed += time.delta_seconds();
+            continue;
+        }
+
+        for effect in &stage.effects {
+            match *effect {
+                FloorEffect::SpawnEnemy {
+                    ref archetype,
+                    point,
+                    offset,
+                    speed,
+                    lifetime,
+                } => {
+                    let Some(archetype) = assets
+                        .archetypes
+                        .get(&assets.map_assets.enemy_archetypes)
+                        .and_then(|archetypes| archetypes.get(archetype))
+                    else {
+                        warn!("Unknown enemy archetype {archetype:?}");
+                        continue;
                     };
 
-                match map.floors[player_floor].action {
-                    FloorAction::Proceed => {
-                        map.floors[player_floor].timer += 1.0;
-                        if map.floors[player_floor].timer == 150.0 {
-                            commands.spawn(AudioBundle {
-                                source: audio_assets.radio_sfx[0].clone(),
-                                settings: PlaybackSettings::REMOVE,
-                            });
-                            map.floors[player_floor].timer = 0.0;
+                    let position = point.resolve(&points, offset);
+                    let enemy = spawn_enemy(
+                        archetype,
+                        &mut commands,
+                        &assets.asset_server,
+                        &mut assets.graphs,
+                        position,
+                        speed,
+                    );
+                    if let Some(sound) = archetype.cues.spawn {
+                        if let Some(source) = sound.source(&assets.audio_assets, &mut *rng) {
+                            play_sound_at(&mut commands, sound.category(), source, position);
                         }
                     }
-                    FloorAction::Radio2 => {
-                        // signal seems to be getting weaker
-                        commands.spawn(AudioBundle {
-                            source: audio_assets.radio_sfx[1].clone(),
-                            settings: PlaybackSettings::REMOVE,
-                        });
-                        map.floors[player_floor].timer = 0.0;
+                    enemies.register(
+                        enemy,
+                        EnemyRecord {
+                            floor: player_floor,
+                            action: floor.action,
+                            lifetime: lifetime
+                                .map(|secs| Timer::from_seconds(secs, TimerMode::Once)),
+                        },
+                    );
+                }
+                FloorEffect::DespawnEnemy => {
+                    enemies.despawn_floor(&mut commands, player_floor);
+                }
+                FloorEffect::SpawnWall { point, offset } => {
+                    let texture = assets.map_assets.brick_wall_texture.clone();
+                    let wall = spawn_blocker(
+                        &mut assets,
+                        &mut commands,
+                        texture,
+                        point.resolve(&points, offset),
+                    );
+                    commands.entity(wall).insert(FloorScoped(player_floor));
+                }
+                FloorEffect::SpawnDoor { point, offset } => {
+                    let texture = assets.map_assets.door_texture.clone();
+                    let door = spawn_blocker(
+                        &mut assets,
+                        &mut commands,
+                        texture,
+                        point.resolve(&points, offset),
+                    );
+                    commands
+                        .entity(door)
+                        .insert((Door, FloorScoped(player_floor)));
+                }
+                FloorEffect::OpenDoors => {
+                    for (entity, scoped) in &d_query {
+                        if scoped.0 == player_floor {
+                            commands.entity(entity).despawn_recursive();
+                        }
                     }
-                    FloorAction::Radio3 => {
-                        // good luck
-                        commands.spawn(AudioBundle {
-                            source: audio_assets.radio_sfx[2].clone(),
-                            settings: PlaybackSettings::REMOVE,
-                        });
-                        map.floors[player_floor].timer = 0.0;
+                }
+                FloorEffect::PlaySound(sound) => {
+                    if let Some(source) = sound.source(&assets.audio_assets, &mut *rng) {
+                        play_sound(&mut commands, sound.category(), source);
                     }
-                    FloorAction::Radio4 => {
-                        // M�RK�ILY�
-                        commands.spawn(AudioBundle {
-                            source: audio_assets.radio_sfx[3].clone(),
-                            settings: PlaybackSettings::REMOVE,
-                        });
-                        map.floors[player_floor].timer = 0.0;
+                }
+                FloorEffect::PlaySoundAt {
+                    sound,
+                    point,
+                    offset,
+                } => {
+                    if let Some(source) = sound.source(&assets.audio_assets, &mut *rng) {
+                        play_sound_at(
+                            &mut commands,
+                            sound.category(),
+                            source,
+                            point.resolve(&points, offset),
+                        );
                     }
-                    FloorAction::Flash => {
-                        // m�rk� vilahtaa k�yt�v�n p��ss�
-                        match map.floors[player_floor].timer {
-                            1.0 => {
-                                if p_transform
-                                    .translation
-                                    .distance(Vec3::new(end_x, floor_y, floor_z))
-                                    < 1.5
-                                {
-                                    *cur_enemy = Some(spawn_enemy(
-                                        &map_assets,
-                                        &mut commands,
-                                        &mut graphs,
-                                        Vec3::new(end_x, floor_y - 0.5, floor_z),
-                                        0.0,
-                                    ));
-                                    let horror_sfx =
-                                        audio_assets.horror_sfx[rng.gen_range(0..2)].clone();
-                                    commands.spawn(AudioBundle {
-                                        source: horror_sfx,
-                                        settings: PlaybackSettings::REMOVE,
-                                    });
-                                    map.floors[player_floor].timer = 5.0;
-                                }
-                            }
-                            2.0 => {
-                                if p_transform
-                                    .translation
-                                    .distance(Vec3::new(floor_x, floor_y, floor_z))
-                                    < 1.5
-                                {
-                                    *cur_enemy = Some(spawn_enemy(
-                                        &map_assets,
-                                        &mut commands,
-                                        &mut graphs,
-                                        Vec3::new(floor_x, floor_y - 0.5, floor_z),
-                                        0.0,
-                                    ));
-                                    let horror_sfx =
-                                        audio_assets.horror_sfx[rng.gen_range(0..2)].clone();
-                                    commands.spawn(AudioBundle {
-                                        source: horror_sfx,
-                                        settings: PlaybackSettings::REMOVE,
-                                    });
-                                    map.floors[player_floor].timer = 5.0;
-                                }
-                            }
-                            3.0 => {
-                                if p_transform
-                                    .translation
-                                    .distance(Vec3::new(start_x, floor_y, floor_z))
-                                    < 1.5
-                                {
-                                    *cur_enemy = Some(spawn_enemy(
-                                        &map_assets,
-                                        &mut commands,
-                                        &mut graphs,
-                                        Vec3::new(start_x, floor_y - 0.5, floor_z),
-                                        0.0,
-                                    ));
-                                    let horror_sfx =
-                                        audio_assets.horror_sfx[rng.gen_range(0..2)].clone();
-                                    commands.spawn(AudioBundle {
-                                        source: horror_sfx,
-                                        settings: PlaybackSettings::REMOVE,
-                                    });
-                                    map.floors[player_floor].timer = 5.0;
-                                }
-                            }
-                            _ => {
-                                map.floors[player_floor].timer += 1.0;
-                                if map.floors[player_floor].timer > 30.0 {
-                                    if let Some(entity) = *cur_enemy {
-                                        commands.entity(entity).despawn_recursive();
-                                    }
+                }
+                FloorEffect::PlaySoundBehind { sound, distance } => {
+                    let Some(source) = sound.source(&assets.audio_assets, &mut *rng) else {
+                        continue;
+                    };
 
-                                    map.floors[player_floor].timer = 0.0;
-                                }
-                            }
-                        }
-                    }
-                    FloorAction::Lights => {
-                        if map.floors[player_floor].timer == 1.0
-                            && p_transform
-                                .translation
-                                .distance(Vec3::new(floor_x, floor_y, floor_z))
-                                < 1.0
-                        {
-                            commands.spawn(AudioBundle {
-                                source: audio_assets.horror_sfx[1].clone(),
-                                settings: PlaybackSettings::REMOVE,
-                            });
-                            commands.spawn(AudioBundle {
-                                source: audio_assets.fire_off.clone(),
-                                settings: PlaybackSettings::REMOVE,
-                            });
-                            map.floors[player_floor].timer = 2.0;
-                            ambient_light.brightness = 45.0;
-                        }
-                    }
-                    FloorAction::Trick1 => {
-                        if map.floors[player_floor].timer == 1.0 {
-                            if (player_floor as f32 / 2.0).floor()
-                                == (player_floor as f32 / 2.0).ceil()
-                            {
-                                // parillinen
-                                if p_transform.translation.distance(Vec3::new(
-                                    start_x - 1.5,
-                                    floor_y - 0.5,
-                                    floor_z - 5.0,
-                                )) < 0.25
-                                {
-                                    // CurrEnemy = CreateEnemy(startx-1.5,FloorY-0.5,FloorZ-2.0,tex173)
-                                    // CurrEnemy\speed = 0.01
-                                    // EntityFX CurrEnemy\obj, 8
-                                    map.floors[player_floor].timer = 2.0;
-                                    commands.spawn(AudioBundle {
-                                        source: audio_assets.horror_sfx[2].clone(),
-                                        settings: PlaybackSettings::REMOVE,
-                                    });
-                                }
-                            } else {
-                                // pariton
-                                if p_transform.translation.distance(Vec3::new(
-                                    start_x + 1.5,
-                                    floor_y - 0.5,
-                                    floor_z + 5.0,
-                                )) < 0.25
-                                {
-                                    // CurrEnemy = CreateEnemy(startx+1.5,FloorY-0.5,FloorZ+2.0,tex173)
-                                    // CurrEnemy\speed = 0.01
-                                    // EntityFX CurrEnemy\obj, 8
-                                    map.floors[player_floor].timer = 2.0;
-                                    commands.spawn(AudioBundle {
-                                        source: audio_assets.horror_sfx[2].clone(),
-                                        settings: PlaybackSettings::REMOVE,
-                                    });
-                                }
-                            }
-                        } /*else if distance2(transform, EntityX(CurrEnemy\collider), EntityY(CurrEnemy\collider), EntityZ(CurrEnemy\collider)) < 0.8 {
-                              player.kill_timer = player.kill_timer.max(1.0);
-                          }*/
+                    for (_, c_transform) in &c_query {
+                        let behind = c_transform.back().with_y(0.0).normalize_or_zero();
+                        play_sound_at(
+                            &mut commands,
+                            sound.category(),
+                            source.clone(),
+                            c_transform.translation() + behind * distance,
+                        );
                     }
-                    FloorAction::Trick2 => {
-                        if map.floors[player_floor].timer == 1.0 {
-                            if (player_floor as f32 / 2.0).floor()
-                                == (player_floor as f32 / 2.0).ceil()
-                            {
-                                // parillinen
-                                if p_transform.translation.distance(Vec3::new(
-                                    start_x + 0.5,
-                                    floor_y - 0.5,
-                                    floor_z - 5.0,
-                                )) < 0.25
-                                {
-                                    // CurrEnemy = CreateEnemy(startx+0.5,FloorY-0.5,FloorZ-2.0,tex173)
-                                    // CurrEnemy\speed = 0.01
-                                    // EntityFX CurrEnemy\obj, 8
-                                    map.floors[player_floor].timer = 2.0;
-                                    commands.spawn(AudioBundle {
-                                        source: audio_assets.horror_sfx[2].clone(),
-                                        settings: PlaybackSettings::REMOVE,
-                                    });
-                                }
-                            } else {
-                                // pariton
-                                if p_transform.translation.distance(Vec3::new(
-                                    start_x - 0.5,
-                                    floor_y - 0.5,
-                                    floor_z + 5.0,
-                                )) < 0.25
-                                {
-                                    // CurrEnemy = CreateEnemy(startx-0.5,FloorY-0.5,FloorZ+2.0,tex173)
-                                    // CurrEnemy\speed = 0.01
-                                    // EntityFX CurrEnemy\obj, 8
-                                    map.floors[player_floor].timer = 2.0;
-                                    commands.spawn(AudioBundle {
-                                        source: audio_assets.horror_sfx[2].clone(),
-                                        settings: PlaybackSettings::REMOVE,
-                                    });
-                                }
-                            }
-                        } /*else if distance2(transform, EntityX(CurrEnemy\collider), EntityY(CurrEnemy\collider), EntityZ(CurrEnemy\collider)) < 0.8 {
-                              player.kill_timer = player.kill_timer.max(1.0);
-                          }*/
+                }
+                FloorEffect::Lighting(transition) => {
+                    lighting.push(transition);
+                }
+                FloorEffect::RestoreLighting(secs) => {
+                    lighting.restore(secs);
+                }
+                FloorEffect::ExtinguishLighter => {
+                    for mut lighter in &mut l_query {
+                        lighter.extinguish();
                     }
-                    FloorAction::Trap => {
-                        if map.floors[player_floor].timer == 1.0 {
-                            let translation = if (player_floor as f32 / 2.0).floor()
-                                == (player_floor as f32 / 2.0).ceil()
-                            {
-                                Vec3::new(end_x + 0.5, floor_y, floor_z) // Even
-                            } else {
-                                Vec3::new(end_x - 0.5, floor_y, floor_z) // Odd
-                            };
-                            commands.spawn((
-                                PbrBundle {
-                                    mesh: meshes.add(Cuboid::new(1.0, 2.0, 1.0)),
-                                    material: materials.add(StandardMaterial {
-                                        base_color_texture: Some(
-                                            map_assets.brick_wall_texture.clone(),
-                                        ),
-                                        ..default()
-                                    }),
-                                    transform: Transform {
-                                        translation,
-                                        rotation: Quat::from_rotation_y(f32::to_radians(-90.0)),
-                                        ..default()
-                                    },
-                                    ..default()
-                                },
-                                Collider::cuboid(1.0, 2.0, 1.0),
-                                RigidBody::Static,
-                            ));
-                            map.floors[player_floor].timer = 2.0;
-                        } else if map.floors[player_floor].timer == 2.0
-                            && p_transform
-                                .translation
-                                .distance(Vec3::new(floor_x, floor_y, floor_z))
-                                < 1.0
-                        {
-                            *cur_enemy = Some(spawn_enemy(
-                                &map_assets,
-                                &mut commands,
-                                &mut graphs,
-                                Vec3::new(start_x, floor_y - 0.5, floor_z),
-                                0.01,
-                            ));
-                            commands.spawn(AudioBundle {
-                                source: audio_assets.horror_sfx[rng.gen_range(0..2)].clone(),
-                                settings: PlaybackSettings::REMOVE,
-                            });
-                            map.floors[player_floor].timer = 3.0;
-                        }
+                }
+                FloorEffect::StarveLighter(secs) => {
+                    for mut lighter in &mut l_query {
+                        lighter.starve = Some(Timer::from_seconds(secs, TimerMode::Once));
                     }
-                    FloorAction::Roar => {
-                        if map.floors[player_floor].timer == 1.0 {
-                            if p_transform
-                                .translation
-                                .distance(Vec3::new(end_x, floor_y, floor_z))
-                                < 6.0
-                            {
-                                // PositionEntity SoundEmitter,FloorX,FloorY-3,FloorZ
-                                // TODO: Make 3d audio
-                                commands.spawn(AudioBundle {
-                                    source: audio_assets.roar_sfx.clone(),
-                                    settings: PlaybackSettings::REMOVE,
-                                });
-                                map.floors[player_floor].timer = 51.0;
-                            }
-                        } else {
-                            map.floors[player_floor].timer += 1.0;
-                            if map.floors[player_floor].timer < 370.0 {
-                                linear_velocity.0 += Vec3::new(
-                                    rng.gen_range(-0.005..0.005),
-                                    rng.gen_range(-0.005..0.005),
-                                    rng.gen_range(-0.005..0.005),
-                                );
-                                c_transform.rotate(Quat::from_euler(
-                                    EulerRot::XYZ,
-                                    f32::to_radians(rng.gen_range(-1.0..1.0)),
-                                    f32::to_radians(rng.gen_range(-1.0..1.0)),
-                                    f32::to_radians(rng.gen_range(-1.0..1.0)),
-                                ));
-                            } else {
-                                map.floors[player_floor].timer = 0.0;
-                            }
-                        }
+                }
+                FloorEffect::ShakeCamera(secs) => {
+                    for (entity, _) in &c_query {
+                        commands.entity(entity).insert(CameraShake {
+                            timer: Timer::from_seconds(secs, TimerMode::Once),
+                        });
                     }
-                    FloorAction::Darkness => {
-                        if map.floors[player_floor].timer == 1.0
-                            && p_transform
-                                .translation
-                                .distance(Vec3::new(floor_x, floor_y, floor_z))
-                                < 1.0
-                        {
-                            let translation = if (player_floor as f32 / 2.0).floor()
-                                == (player_floor as f32 / 2.0).ceil()
-                            {
-                                Vec3::new(start_x - 0.5, floor_y, floor_z) // Even
-                            } else {
-                                Vec3::new(start_x + 0.5, floor_y, floor_z) // Odd
-                            };
-                            commands.spawn((
-                                PbrBundle {
-                                    mesh: meshes.add(Cuboid::new(1.0, 2.0, 1.0)),
-                                    material: materials.add(StandardMaterial {
-                                        base_color_texture: Some(
-                                            map_assets.brick_wall_texture.clone(),
-                                        ),
-                                        ..default()
-                                    }),
-                                    transform: Transform {
-                                        translation,
-                                        rotation: Quat::from_rotation_y(f32::to_radians(-90.0)),
-                                        ..default()
-                                    },
-                                    ..default()
-                                },
-                                Collider::cuboid(1.0, 2.0, 1.0),
-                                RigidBody::Static,
-                            ));
+                }
+            }
+        }
 
-                            let translation = if (player_floor as f32 / 2.0).floor()
-                                == (player_floor as f32 / 2.0).ceil()
-                            {
-                                Vec3::new(end_x + 0.5, floor_y, floor_z) // Even
-                            } else {
-                                Vec3::new(end_x - 0.5, floor_y, floor_z) // Odd
-                            };
-                            commands.spawn((
-                                PbrBundle {
-                                    mesh: meshes.add(Cuboid::new(1.0, 2.0, 1.0)),
-                                    material: materials.add(StandardMaterial {
-                                        base_color_texture: Some(
-                                            map_assets.brick_wall_texture.clone(),
-                                        ),
-                                        ..default()
-                                    }),
-                                    transform: Transform {
-                                        translation,
-                                        rotation: Quat::from_rotation_y(f32::to_radians(-90.0)),
-                                        ..default()
-                                    },
-                                    ..default()
-                                },
-                                Collider::cuboid(1.0, 2.0, 1.0),
-                                RigidBody::Static,
-                            ));
+        floor.stage = stage.next;
+        floor.elapsed = 0.0;
+    }
+}
 
-                            commands.spawn(AudioBundle {
-                                source: audio_assets.stone_sfx.clone(),
-                                settings: PlaybackSettings::REMOVE,
-                            });
-                            map.floors[player_floor].timer = 2.0;
-                        } else if map.floors[player_floor].timer < 600.0 {
-                            map.floors[player_floor].timer += 1.0;
-                            // temp#=max(Brightness-(map.floors[player_floor].timer/600.0)*Brightness,10)
-                            // AmbientLight temp,temp,temp
+/// Despawns what the previous floor left behind and undoes its floor scoped lighting
+pub fn leave_floor(
+    mut commands: Commands,
+    p_query: Query<(&Player, Has<Dying>)>,
+    f_query: Query<(Entity, &FloorScoped)>,
+    mut lighting: ResMut<Lighting>,
+    mut last_floor: Local<Option<usize>>,
+) {
+    let Ok((player, dying)) = p_query.get_single() else {
+        return;
+    };
 
-                            if map.floors[player_floor].timer == 600.0 {
-                                *cur_enemy = Some(spawn_enemy(
-                                    &map_assets,
-                                    &mut commands,
-                                    &mut graphs,
-                                    Vec3::new(floor_x, floor_y - 0.5, floor_z),
-                                    0.01,
-                                ));
-                                commands.spawn(AudioBundle {
-                                    source: audio_assets.horror_sfx[rng.gen_range(0..2)].clone(),
-                                    settings: PlaybackSettings::REMOVE,
-                                });
-                                map.floors[player_floor].timer = 601.0;
-                            }
-                        } /*else if Distance2(EntityX(CurrEnemy\collider),EntityY(CurrEnemy\collider),EntityZ(CurrEnemy\collider)) < 0.7 {
-                              KillTimer = max(KillTimer,1)
-                          }*/
-                    }
-                    _ => {}
-                }
-            }
+    if *last_floor == Some(player.floor_index) {
+        return;
+    }
+
+    // Leaves the red fade of a dying player alone
+    if last_floor.is_some() && lighting.is_floor_scoped() && !dying {
+        lighting.restore(1.0);
+    }
+    *last_floor = Some(player.floor_index);
+
+    for (entity, scoped) in &f_query {
+        if scoped.0 != player.floor_index {
+            commands.entity(entity).despawn_recursive();
         }
     }
 }
 
+/// Spawns a wall or door that blocks the stairwell
+fn spawn_blocker(
+    assets: &mut FloorAssets,
+    commands: &mut Commands,
+    texture: Handle<Image>,
+    translation: Vec3,
+) -> Entity {
+    commands
+        .spawn((
+            PbrBundle {
+                mesh: assets.meshes.add(Cuboid::new(1.0, 2.0, 1.0)),
+                material: assets.materials.add(StandardMaterial {
+                    base_color_texture: Some(texture),
+                    ..default()
+                }),
+                transform: Transform {
+                    translation,
+                    rotation: Quat::from_rotation_y(f32::to_radians(-90.0)),
+                    ..default()
+                },
+                ..default()
+            },
+            Collider::cuboid(1.0, 2.0, 1.0),
+            RigidBody::Static,
+            StateScoped(AppState::Game),
+        ))
+        .id()
+}
+
 pub fn floor_transform(i: usize) -> Transform {
     let mut transform = Transform::default();
 
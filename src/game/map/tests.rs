@@ -0,0 +1,552 @@
+use super::{
+    components::{Door, FloorScoped},
+    events::{FloorPoint, FloorSound},
+    resources::{Floor, FloorAction, FloorPoints, Map, MapSeed},
+    systems::update_floors,
+};
+use crate::{
+    game::{
+        audio::{components::SoundCategory, systems::limit_voices},
+        enemy::{components::Enemy, resources::EnemyRegistry},
+        lighting::{resources::Lighting, systems::update_lighting},
+        player::components::{CameraShake, Lighter, Player, PlayerCamera},
+        testing::{run_for, stub_audio_assets, test_app},
+    },
+    resources::AudioAssets,
+};
+use avian3d::prelude::*;
+use bevy::prelude::*;
+use bevy_rand::prelude::*;
+use rand::SeedableRng;
+
+/// Runs the script of `action` on a floor, with the player standing at its start
+struct FloorHarness {
+    app: App,
+    floor: usize,
+    points: FloorPoints,
+    player: Entity,
+    camera: Entity,
+}
+
+impl FloorHarness {
+    fn new(floor: usize, action: FloorAction) -> Self {
+        let mut map = Map::default();
+        map.floors[floor] = Floor {
+            action,
+            stage: Some(0),
+            elapsed: 0.0,
+        };
+        Self::with_map(map, floor)
+    }
+
+    /// Puts the player at the start of `floor` of an existing map
+    fn with_map(map: Map, floor: usize) -> Self {
+        let mut app = test_app();
+        app.add_systems(Update, (update_floors, update_lighting).chain())
+            .add_systems(PostUpdate, limit_voices);
+        app.insert_resource(map);
+
+        let points = FloorPoints::new(floor);
+        let world = app.world_mut();
+        let player = world
+            .spawn((
+                Player {
+                    floor_index: floor,
+                    ..default()
+                },
+                Transform::default(),
+            ))
+            .id();
+        let camera = world
+            .spawn((
+                PlayerCamera::default(),
+                Transform::default(),
+                GlobalTransform::default(),
+            ))
+            .id();
+        world.spawn(Lighter::default());
+
+        let mut harness = Self {
+            app,
+            floor,
+            points,
+            player,
+            camera,
+        };
+        harness.walk_to(points.start);
+        harness
+    }
+
+    /// Moves the player, looking down the corridor towards its end
+    fn walk_to(&mut self, translation: Vec3) {
+        let world = self.app.world_mut();
+        world.get_mut::<Transform>(self.player).unwrap().translation = translation;
+
+        let mut end = self.points.end;
+        end.y = translation.y;
+        let look = Transform::from_translation(translation).looking_at(end, Vec3::Y);
+        world
+            .entity_mut(self.camera)
+            .insert((look, GlobalTransform::from(look)));
+    }
+
+    fn point(&self, point: FloorPoint, offset: Vec3) -> Vec3 {
+        point.resolve(&self.points, offset)
+    }
+
+    fn update(&mut self) {
+        self.app.update();
+    }
+
+    fn run_for(&mut self, secs: f32) {
+        run_for(&mut self.app, secs);
+    }
+
+    fn stage(&self) -> Option<usize> {
+        self.app.world().resource::<Map>().floors[self.floor].stage
+    }
+
+    fn audio(&self) -> &AudioAssets {
+        self.app.world().resource::<AudioAssets>()
+    }
+
+    fn lighting(&self) -> &Lighting {
+        self.app.world().resource::<Lighting>()
+    }
+
+    /// Position and speed of every enemy
+    fn enemies(&mut self) -> Vec<(Vec3, f32)> {
+        let world = self.app.world_mut();
+        world
+            .query::<(&Enemy, &Position)>()
+            .iter(world)
+            .map(|(enemy, position)| (position.0, enemy.speed))
+            .collect()
+    }
+
+    /// Position of every wall and door, and whether it is a door
+    fn blockers(&mut self) -> Vec<(Vec3, bool)> {
+        let world = self.app.world_mut();
+        world
+            .query_filtered::<(&FloorScoped, &Transform, Has<Door>), With<Collider>>()
+            .iter(world)
+            .map(|(scoped, transform, door)| {
+                assert_eq!(scoped.0, self.floor);
+                (transform.translation, door)
+            })
+            .collect()
+    }
+
+    /// Source of every sound played, with its position if it is placed in the world
+    fn sounds(&mut self) -> Vec<(Handle<AudioSource>, Option<Vec3>)> {
+        let world = self.app.world_mut();
+        world
+            .query_filtered::<(&Handle<AudioSource>, Option<&Transform>), With<SoundCategory>>()
+            .iter(world)
+            .map(|(source, transform)| (source.clone(), transform.map(|t| t.translation)))
+            .collect()
+    }
+
+    fn is_horror(&self, source: &Handle<AudioSource>) -> bool {
+        self.audio().horror_sfx.contains(source)
+    }
+}
+
+fn assert_near(a: Vec3, b: Vec3) {
+    assert!(a.distance(b) < 1e-3, "{a} is not near {b}");
+}
+
+#[test]
+fn flash_appears_at_the_end_and_vanishes() {
+    let mut floor = FloorHarness::new(2, FloorAction::Flash);
+    floor.update();
+    assert_eq!(floor.stage(), Some(0));
+    assert!(floor.enemies().is_empty());
+
+    floor.walk_to(floor.points.end);
+    floor.update();
+    assert_eq!(floor.stage(), Some(1));
+
+    let enemies = floor.enemies();
+    assert_eq!(enemies.len(), 1);
+    assert_near(
+        enemies[0].0,
+        floor.point(FloorPoint::End, Vec3::new(0.0, -0.5, 0.0)),
+    );
+    assert_eq!(enemies[0].1, 0.0);
+
+    let sounds = floor.sounds();
+    assert_eq!(sounds.len(), 1);
+    assert!(floor.is_horror(&sounds[0].0));
+    assert_eq!(sounds[0].1, None);
+
+    floor.run_for(0.5);
+    assert_eq!(floor.stage(), None);
+    assert!(floor.enemies().is_empty());
+    assert!(floor
+        .app
+        .world()
+        .resource::<EnemyRegistry>()
+        .enemies
+        .is_empty());
+}
+
+#[test]
+fn trap_walls_the_way_and_spawns_behind() {
+    let mut floor = FloorHarness::new(3, FloorAction::Trap);
+    floor.update();
+    assert_eq!(floor.stage(), Some(1));
+
+    let blockers = floor.blockers();
+    assert_eq!(blockers.len(), 1);
+    assert_near(
+        blockers[0].0,
+        floor.point(FloorPoint::End, Vec3::new(0.5, 0.0, 0.0)),
+    );
+    assert!(!blockers[0].1);
+    assert!(floor.enemies().is_empty());
+
+    floor.walk_to(floor.points.mid);
+    floor.update();
+    assert_eq!(floor.stage(), None);
+
+    let enemies = floor.enemies();
+    assert_eq!(enemies.len(), 1);
+    assert_near(
+        enemies[0].0,
+        floor.point(FloorPoint::Start, Vec3::new(0.0, -0.5, 0.0)),
+    );
+    assert_eq!(enemies[0].1, 1.5);
+
+    let sounds = floor.sounds();
+    assert_eq!(sounds.len(), 1);
+    assert!(floor.is_horror(&sounds[0].0));
+}
+
+#[test]
+fn darkness_closes_in_before_the_monster_comes() {
+    let mut floor = FloorHarness::new(2, FloorAction::Darkness);
+    floor.update();
+    assert_eq!(floor.stage(), Some(0));
+
+    floor.walk_to(floor.points.mid);
+    floor.update();
+    assert_eq!(floor.stage(), Some(1));
+
+    let mut blockers = floor.blockers();
+    blockers.sort_by(|a, b| a.0.x.total_cmp(&b.0.x));
+    assert_eq!(blockers.len(), 2);
+    assert_near(
+        blockers[0].0,
+        floor.point(FloorPoint::End, Vec3::new(0.5, 0.0, 0.0)),
+    );
+    assert_near(
+        blockers[1].0,
+        floor.point(FloorPoint::Start, Vec3::new(-0.5, 0.0, 0.0)),
+    );
+
+    let sounds = floor.sounds();
+    assert_eq!(sounds.len(), 1);
+    assert_eq!(sounds[0].0, floor.audio().stone_sfx);
+    assert_near(sounds[0].1.unwrap(), floor.points.mid);
+
+    let world = floor.app.world_mut();
+    let lighter = world.query::<&Lighter>().single(world);
+    assert!(lighter.starve.is_some());
+    assert!(floor.lighting().is_floor_scoped());
+    assert!(floor.enemies().is_empty());
+
+    floor.run_for(10.1);
+    assert_eq!(floor.stage(), None);
+    assert!((floor.lighting().current.brightness - 10.0).abs() < 1e-3);
+    assert!((floor.lighting().current.fog_distance - 1.5).abs() < 1e-3);
+
+    let enemies = floor.enemies();
+    assert_eq!(enemies.len(), 1);
+    assert_near(
+        enemies[0].0,
+        floor.point(FloorPoint::Mid, Vec3::new(0.0, -0.5, 0.0)),
+    );
+}
+
+#[test]
+fn roar_comes_from_below_and_shakes_the_camera() {
+    let mut floor = FloorHarness::new(2, FloorAction::Roar);
+    floor.update();
+    assert_eq!(floor.stage(), Some(0));
+    assert!(floor.sounds().is_empty());
+
+    floor.walk_to(floor.points.mid);
+    floor.update();
+    assert_eq!(floor.stage(), None);
+
+    let sounds = floor.sounds();
+    assert_eq!(sounds.len(), 1);
+    assert_eq!(sounds[0].0, floor.audio().roar_sfx);
+    assert_near(
+        sounds[0].1.unwrap(),
+        floor.points.mid - Vec3::new(0.0, 3.0, 0.0),
+    );
+    assert!(floor.app.world().get::<CameraShake>(floor.camera).is_some());
+}
+
+/// Where a sound `distance` behind the player at the middle of the floor is played
+fn behind_mid(floor: &FloorHarness, distance: f32) -> Vec3 {
+    let back = (floor.points.start - floor.points.mid).normalize();
+    floor.points.mid + back * distance
+}
+
+#[test]
+fn steps_come_down_the_stairs() {
+    let mut floor = FloorHarness::new(2, FloorAction::Steps);
+    floor.update();
+    assert!(floor.sounds().is_empty());
+
+    floor.walk_to(floor.points.mid);
+    floor.update();
+    assert_eq!(floor.stage(), Some(1));
+    assert_eq!(floor.sounds().len(), 1);
+
+    floor.run_for(2.0);
+    assert_eq!(floor.stage(), None);
+
+    let mut sounds = floor.sounds();
+    sounds.sort_by(|a, b| b.1.unwrap().y.total_cmp(&a.1.unwrap().y));
+    assert_eq!(sounds.len(), 4);
+    for (i, (source, position)) in sounds.iter().enumerate() {
+        assert_eq!(*source, floor.audio().step_sound);
+        let height = 2.0 - i as f32 * 0.5;
+        assert_near(
+            position.unwrap(),
+            floor.point(FloorPoint::Start, Vec3::new(0.0, height, 0.0)),
+        );
+    }
+}
+
+#[test]
+fn run_closes_in_from_behind() {
+    let mut floor = FloorHarness::new(3, FloorAction::Run);
+    floor.walk_to(floor.points.mid);
+    floor.update();
+    assert_eq!(floor.stage(), Some(1));
+
+    floor.run_for(1.2);
+    assert_eq!(floor.stage(), None);
+
+    let sounds = floor.sounds();
+    assert_eq!(sounds.len(), 5);
+
+    let mut steps: Vec<_> = sounds
+        .iter()
+        .filter(|(source, _)| *source == floor.audio().loud_step_sound)
+        .map(|(_, position)| position.unwrap())
+        .collect();
+    steps.sort_by(|a, b| {
+        let mid = floor.points.mid;
+        b.distance(mid).total_cmp(&a.distance(mid))
+    });
+    assert_eq!(steps.len(), 4);
+    for (step, distance) in steps.into_iter().zip([4.0, 3.0, 2.0, 1.0]) {
+        assert_near(step, behind_mid(&floor, distance));
+    }
+
+    assert_eq!(
+        sounds
+            .iter()
+            .filter(|(source, position)| floor.is_horror(source) && position.is_none())
+            .count(),
+        1
+    );
+}
+
+#[test]
+fn breath_is_right_behind() {
+    let mut floor = FloorHarness::new(2, FloorAction::Breath);
+    floor.walk_to(floor.points.mid);
+    floor.update();
+    assert_eq!(floor.stage(), None);
+
+    let sounds = floor.sounds();
+    assert_eq!(sounds.len(), 1);
+    assert_eq!(sounds[0].0, floor.audio().breath_sfx);
+    assert_near(sounds[0].1.unwrap(), behind_mid(&floor, 0.5));
+}
+
+#[test]
+fn cell_holds_the_monster_for_a_while() {
+    let mut floor = FloorHarness::new(3, FloorAction::Cell);
+    floor.walk_to(floor.points.mid);
+    floor.update();
+    assert_eq!(floor.stage(), Some(1));
+
+    let enemies = floor.enemies();
+    assert_eq!(enemies.len(), 1);
+    assert_near(
+        enemies[0].0,
+        floor.point(FloorPoint::Mid, Vec3::new(0.0, -0.5, -2.5)),
+    );
+    assert_eq!(enemies[0].1, 0.0);
+    let sounds = floor.sounds();
+    assert_eq!(sounds.len(), 1);
+    assert!(floor.is_horror(&sounds[0].0));
+
+    floor.run_for(4.0);
+    assert_eq!(floor.enemies().len(), 1);
+
+    floor.run_for(1.1);
+    assert_eq!(floor.stage(), None);
+    assert!(floor.enemies().is_empty());
+}
+
+#[test]
+fn lock_opens_from_the_other_side() {
+    let mut floor = FloorHarness::new(2, FloorAction::Lock);
+    floor.update();
+    assert_eq!(floor.stage(), Some(1));
+
+    let blockers = floor.blockers();
+    assert_eq!(blockers.len(), 1);
+    assert_near(
+        blockers[0].0,
+        floor.point(FloorPoint::End, Vec3::new(0.5, 0.0, 0.0)),
+    );
+    assert!(blockers[0].1);
+
+    floor.walk_to(floor.points.end);
+    floor.update();
+    assert_eq!(floor.stage(), Some(2));
+
+    let sounds = floor.sounds();
+    assert_eq!(sounds.len(), 1);
+    assert_eq!(sounds[0].0, floor.audio().loud_step_sound);
+    assert_near(
+        sounds[0].1.unwrap(),
+        floor.point(FloorPoint::End, Vec3::new(1.0, 0.0, 0.0)),
+    );
+    assert!(floor.lighting().is_floor_scoped());
+
+    floor.run_for(1.0);
+    assert!((floor.lighting().current.brightness - 20.0).abs() < 1e-3);
+
+    floor.run_for(2.1);
+    assert_eq!(floor.stage(), None);
+    assert!(floor.blockers().is_empty());
+    assert!(floor
+        .sounds()
+        .iter()
+        .any(|(source, _)| *source == floor.audio().stone_sfx));
+
+    floor.run_for(1.1);
+    assert!(!floor.lighting().is_floor_scoped());
+    assert!((floor.lighting().current.brightness - 80.0).abs() < 1e-3);
+}
+
+#[test]
+fn scp173_waits_at_the_end() {
+    let mut floor = FloorHarness::new(3, FloorAction::Scp173);
+    floor.update();
+    assert!(floor.enemies().is_empty());
+
+    floor.walk_to(floor.points.mid);
+    floor.update();
+    assert_eq!(floor.stage(), None);
+
+    let enemies = floor.enemies();
+    assert_eq!(enemies.len(), 1);
+    assert_near(
+        enemies[0].0,
+        floor.point(FloorPoint::End, Vec3::new(0.0, -0.5, 0.0)),
+    );
+    assert_eq!(enemies[0].1, 2.0);
+
+    let sounds = floor.sounds();
+    assert_eq!(sounds.len(), 1);
+    assert_eq!(sounds[0].0, floor.audio().stone_sfx);
+    assert_near(sounds[0].1.unwrap(), floor.points.end);
+}
+
+#[test]
+fn generated_filler_floors_stay_dormant() {
+    let mut rng = WyRand::seed_from_u64(87);
+    let mut map = Map::default();
+    map.generate(&mut rng);
+
+    let active: Vec<_> = map
+        .floors
+        .iter()
+        .enumerate()
+        .filter(|(_, floor)| floor.stage.is_some())
+        .map(|(i, floor)| (i, floor.action))
+        .collect();
+
+    // Only the scripted floors run their events, at most one per assignment in `generate`
+    assert!(active.len() <= 18, "{active:?}");
+    assert!(active.contains(&(1, FloorAction::Proceed)));
+    assert!(active.contains(&(7, FloorAction::Lock)));
+    assert!(active
+        .iter()
+        .any(|&(i, action)| action == FloorAction::Darkness && (150..200).contains(&i)));
+
+    for action in [FloorAction::Cell, FloorAction::Trap, FloorAction::Roar] {
+        assert!(active.iter().any(|&(_, a)| a == action), "{action:?}");
+    }
+}
+
+#[test]
+fn generated_cell_floor_fires() {
+    let mut rng = WyRand::seed_from_u64(87);
+    let mut map = Map::default();
+    map.generate(&mut rng);
+
+    let cell = map
+        .floors
+        .iter()
+        .position(|floor| floor.action == FloorAction::Cell && floor.stage.is_some())
+        .expect("a scripted Cell floor");
+
+    let mut floor = FloorHarness::with_map(map, cell);
+    floor.update();
+    assert_eq!(floor.stage(), Some(0));
+
+    floor.walk_to(floor.points.mid);
+    floor.update();
+    assert_eq!(floor.stage(), Some(1));
+    assert_eq!(floor.enemies().len(), 1);
+}
+
+#[test]
+fn unknown_sounds_are_skipped() {
+    let audio = stub_audio_assets();
+    let mut rng = WyRand::seed_from_u64(87);
+
+    assert_eq!(
+        FloorSound::Horror(2).source(&audio, &mut rng),
+        Some(audio.horror_sfx[2].clone())
+    );
+    assert_eq!(FloorSound::Horror(3).source(&audio, &mut rng), None);
+    assert_eq!(FloorSound::Radio(9).source(&audio, &mut rng), None);
+}
+
+#[test]
+fn same_seed_generates_the_same_map() {
+    // Seeded the way `spawn_map` does it
+    let generate = |seed: MapSeed| {
+        let mut map = Map::default();
+        map.generate(&mut WyRand::seed_from_u64(seed.0));
+
+        let floors: Vec<_> = map
+            .floors
+            .iter()
+            .map(|floor| (floor.action, floor.stage))
+            .collect();
+        let rooms: Vec<_> = map
+            .rooms
+            .into_iter()
+            .map(|room| (room.kind, room.label))
+            .collect();
+        (floors, rooms)
+    };
+
+    assert_eq!(generate(MapSeed(87)), generate(MapSeed(87)));
+    assert_ne!(generate(MapSeed(87)), generate(MapSeed(88)));
+}
@@ -1,17 +1,27 @@
 use crate::AppState;
+use audio::SoundPlugin;
 use bevy::prelude::*;
 use enemy::EnemyPlugin;
 use glimpse::GlimpsePlugin;
 use leafwing_input_manager::prelude::*;
+use lighting::LightingPlugin;
 use map::MapPlugin;
 use player::{resources::PlayerAction, PlayerPlugin};
+use replay::ReplayPlugin;
+use save::SavePlugin;
 use systems::*;
 
-mod enemy;
+mod audio;
+pub mod enemy;
 mod glimpse;
-mod map;
-mod player;
+mod lighting;
+pub mod map;
+pub mod player;
+mod replay;
+pub mod save;
 mod systems;
+#[cfg(test)]
+mod testing;
 
 pub struct GamePlugin;
 
@@ -20,13 +30,14 @@ impl Plugin for GamePlugin {
         app.add_plugins((
             EnemyPlugin,
             GlimpsePlugin,
+            LightingPlugin,
             MapPlugin,
             PlayerPlugin,
+            SavePlugin,
+            ReplayPlugin,
+            SoundPlugin,
             InputManagerPlugin::<PlayerAction>::default(),
         ))
-        .add_systems(
-            OnEnter(AppState::Game),
-            (spawn_map, spawn_player, spawn_glimpses).chain(),
-        );
+        .add_systems(OnEnter(AppState::Game), (spawn_map, spawn_player).chain());
     }
 }
@@ -6,7 +6,11 @@ use super::{components::*, resources::*};
 #[derive(Bundle)]
 pub struct PlayerBundle {
     pub player: Player,
+    pub player_input: PlayerInput,
     pub player_footsteps: PlayerFootsteps,
+    pub player_fall: PlayerFall,
+    pub stamina: Stamina,
+    pub controller: CharacterController,
     pub input_bundle: InputManagerBundle<PlayerAction>,
 }
 
@@ -14,17 +18,12 @@ impl Default for PlayerBundle {
     fn default() -> Self {
         Self {
             player: Default::default(),
+            player_input: Default::default(),
             player_footsteps: Default::default(),
-            input_bundle: InputManagerBundle::<PlayerAction> {
-                action_state: ActionState::default(),
-                input_map: InputMap::new([
-                    (PlayerAction::MoveUp, KeyCode::KeyW),
-                    (PlayerAction::MoveDown, KeyCode::KeyS),
-                    (PlayerAction::MoveLeft, KeyCode::KeyA),
-                    (PlayerAction::MoveRight, KeyCode::KeyD),
-                ])
-                .with_dual_axis(PlayerAction::MouseMotion, MouseMove::default()),
-            },
+            player_fall: Default::default(),
+            stamina: Default::default(),
+            controller: Default::default(),
+            input_bundle: InputManagerBundle::with_map(Controls::default().input_map()),
         }
     }
 }
@@ -4,7 +4,9 @@ use bevy::prelude::*;
 
 #[derive(Component)]
 pub struct Player {
+    /// Starts the death sequence once it is above zero
     pub kill_timer: f32,
+    pub time_alive: f32,
     pub floor_index: usize,
     pub camera_height: Vec3,
     pub mouse_sensitivity: f32,
@@ -15,6 +17,7 @@ impl Default for Player {
     fn default() -> Self {
         Self {
             kill_timer: 0.0,
+            time_alive: 0.0,
             floor_index: 1,
             camera_height: Vec3::Y * 0.7,
             mouse_sensitivity: 0.003,
@@ -23,6 +26,108 @@ impl Default for Player {
     }
 }
 
+/// Movement intent and look angles of a player, written by `player_input`
+#[derive(Component, Default)]
+pub struct PlayerInput {
+    pub movement: Vec3,
+    pub gait: Gait,
+    pub pitch: f32,
+    pub yaw: f32,
+}
+
+#[derive(Default, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Gait {
+    #[default]
+    Walk,
+    Sprint,
+    Crouch,
+}
+
+impl Gait {
+    pub fn speed_scale(&self) -> f32 {
+        match self {
+            Self::Walk => 1.0,
+            Self::Sprint => 1.8,
+            Self::Crouch => 0.5,
+        }
+    }
+}
+
+/// Drained by sprinting, the player can't sprint again until it is back above `breathless`
+#[derive(Component)]
+pub struct Stamina {
+    /// Between 0 and 1
+    pub current: f32,
+    /// Per second while sprinting
+    pub drain: f32,
+    /// Per second while not sprinting
+    pub recovery: f32,
+    /// Below this the player is out of breath
+    pub breathless: f32,
+    pub exhausted: bool,
+    pub breath_timer: Timer,
+}
+
+impl Default for Stamina {
+    fn default() -> Self {
+        Self {
+            current: 1.0,
+            drain: 0.15,
+            recovery: 0.1,
+            breathless: 0.3,
+            exhausted: false,
+            breath_timer: Timer::new(Duration::from_secs_f32(2.5), TimerMode::Repeating),
+        }
+    }
+}
+
+/// Marks a player standing on ground that is not too steep to walk on
+#[derive(Component)]
+pub struct Grounded;
+
+/// Tuning of the kinematic character controller
+#[derive(Component)]
+pub struct CharacterController {
+    /// Steepest walkable slope, in radians
+    pub max_slope_angle: f32,
+    /// Tallest ledge that is stepped onto instead of blocking the way
+    pub step_height: f32,
+    /// How far down the player sticks to the ground when walking off a ledge
+    pub snap_distance: f32,
+    pub gravity: f32,
+}
+
+impl Default for CharacterController {
+    fn default() -> Self {
+        Self {
+            max_slope_angle: 50.0f32.to_radians(),
+            step_height: 0.35,
+            snap_distance: 0.35,
+            gravity: 9.81,
+        }
+    }
+}
+
+/// Tracks the height of the current fall, measured from its highest point
+#[derive(Component)]
+pub struct PlayerFall {
+    /// Drop in metres that lands with a loud step and a camera shake
+    pub hard_landing: f32,
+    /// Drop in metres that kills the player
+    pub lethal: f32,
+    pub peak: Option<f32>,
+}
+
+impl Default for PlayerFall {
+    fn default() -> Self {
+        Self {
+            hard_landing: 1.2,
+            lethal: 4.5,
+            peak: None,
+        }
+    }
+}
+
 #[derive(Component)]
 pub struct PlayerFootsteps {
     pub timer: Timer,
@@ -42,6 +147,13 @@ pub struct PlayerCamera {
     pub max_bob: Vec3,
     pub tilt: f32,
     pub timer: f32,
+    /// Bob and tilt multipliers while sprinting and crouching
+    pub sprint_bob: f32,
+    pub crouch_bob: f32,
+    /// How far the camera drops while crouching
+    pub crouch_height: f32,
+    /// Current crouch amount between 0 and 1, eased towards the gait
+    pub crouch: f32,
 }
 
 impl Default for PlayerCamera {
@@ -51,6 +163,73 @@ impl Default for PlayerCamera {
             max_bob: Vec3::splat(0.07),
             tilt: 0.5f32.to_radians(),
             timer: 0.0,
+            sprint_bob: 1.6,
+            crouch_bob: 0.5,
+            crouch_height: 0.4,
+            crouch: 0.0,
         }
     }
 }
+
+impl PlayerCamera {
+    pub fn bob_scale(&self, gait: Gait) -> f32 {
+        match gait {
+            Gait::Walk => 1.0,
+            Gait::Sprint => self.sprint_bob,
+            Gait::Crouch => self.crouch_bob,
+        }
+    }
+}
+
+/// Shakes the camera and jolts the player until the timer finishes
+#[derive(Component)]
+pub struct CameraShake {
+    pub timer: Timer,
+}
+
+/// Collapses the camera and fades the light to red until the timer finishes
+#[derive(Component)]
+pub struct Dying {
+    pub timer: Timer,
+}
+
+impl Default for Dying {
+    fn default() -> Self {
+        Self {
+            timer: Timer::new(Duration::from_secs_f32(3.0), TimerMode::Once),
+        }
+    }
+}
+
+/// Handheld flame lighting the way, a child of the `PlayerCamera`
+#[derive(Component)]
+pub struct Lighter {
+    pub lit: bool,
+    /// Seconds of flame left
+    pub fuel: f32,
+    /// Intensity of a steady, full flame
+    pub intensity: f32,
+    /// Current flicker multiplier
+    pub flicker: f32,
+    /// Slowly smothers the flame until it is relit
+    pub starve: Option<Timer>,
+}
+
+impl Default for Lighter {
+    fn default() -> Self {
+        Self {
+            lit: false,
+            fuel: 180.0,
+            intensity: 2000.0,
+            flicker: 1.0,
+            starve: None,
+        }
+    }
+}
+
+impl Lighter {
+    pub fn extinguish(&mut self) {
+        self.lit = false;
+        self.starve = None;
+    }
+}
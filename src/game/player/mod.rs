@@ -1,12 +1,13 @@
 use crate::AppState;
+use avian3d::prelude::*;
 use bevy::prelude::*;
-use resources::PlayerInput;
+use resources::Controls;
 use systems::*;
 
 pub mod bundles;
 pub mod components;
 pub mod resources;
-mod systems;
+pub mod systems;
 
 pub const ANGLE_EPSILON: f32 = 0.001953125;
 
@@ -14,20 +15,29 @@ pub struct PlayerPlugin;
 
 impl Plugin for PlayerPlugin {
     fn build(&self, app: &mut App) {
-        app.init_resource::<PlayerInput>()
+        app.insert_resource(Controls::load())
             .add_systems(OnEnter(AppState::Game), player_spawn)
+            .add_systems(OnExit(AppState::Game), player_despawn)
+            .add_systems(PostProcessCollisions, player_collisions)
             .add_systems(
                 Update,
                 (
                     player_input,
-                    player_move,
+                    player_pause,
+                    player_stamina.after(player_input),
+                    player_move.after(player_ground).after(player_stamina),
                     player_look,
+                    player_shake.after(player_look),
+                    player_lighter,
+                    player_interact,
                     player_footsteps,
                     player_label_floor,
                     player_cull_floor,
-                    player_death,
-                    player_ambience,
-                    // player_fall_damage,
+                    player_enemy_contact,
+                    player_death.after(player_enemy_contact),
+                    player_dying.after(player_look).after(player_move),
+                    player_ground,
+                    player_fall_damage.after(player_ground),
                 )
                     .run_if(in_state(AppState::Game)),
             );
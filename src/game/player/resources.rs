@@ -1,28 +1,187 @@
 use bevy::prelude::*;
 use leafwing_input_manager::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, fs};
 
-#[derive(PartialEq, Eq, Clone, Copy, Hash, Debug, Reflect)]
+pub const CONTROLS_PATH: &str = "controls.ron";
+
+#[derive(PartialEq, Eq, Clone, Copy, Hash, Debug, Reflect, Serialize, Deserialize)]
 pub enum PlayerAction {
     MouseMotion,
+    Move,
+    Look,
     MoveUp,
     MoveDown,
     MoveLeft,
     MoveRight,
+    Sprint,
+    Crouch,
+    Lighter,
+    Interact,
+    Pause,
+    QuickSave,
 }
 
 impl Actionlike for PlayerAction {
     fn input_control_kind(&self) -> InputControlKind {
         match self {
-            Self::MouseMotion => InputControlKind::DualAxis,
+            Self::MouseMotion | Self::Move | Self::Look => InputControlKind::DualAxis,
             _ => InputControlKind::Button,
         }
     }
 }
 
-// TODO: Remove this
-#[derive(Resource, Default)]
-pub struct PlayerInput {
-    pub movement: Vec3,
-    pub pitch: f32,
-    pub yaw: f32,
+impl PlayerAction {
+    /// Actions that can be rebound to a key from the controls menu
+    pub const REBINDABLE: [Self; 10] = [
+        Self::MoveUp,
+        Self::MoveDown,
+        Self::MoveLeft,
+        Self::MoveRight,
+        Self::Sprint,
+        Self::Crouch,
+        Self::Lighter,
+        Self::Interact,
+        Self::Pause,
+        Self::QuickSave,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::MouseMotion => "Look (Mouse)",
+            Self::Move => "Move (Stick)",
+            Self::Look => "Look (Stick)",
+            Self::MoveUp => "Move Forward",
+            Self::MoveDown => "Move Backward",
+            Self::MoveLeft => "Move Left",
+            Self::MoveRight => "Move Right",
+            Self::Sprint => "Sprint",
+            Self::Crouch => "Crouch",
+            Self::Lighter => "Lighter",
+            Self::Interact => "Interact",
+            Self::Pause => "Pause",
+            Self::QuickSave => "Quick Save",
+        }
+    }
+}
+
+/// Key and gamepad bindings, stored in `controls.ron`
+#[derive(Resource, Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct Controls {
+    pub keys: HashMap<PlayerAction, KeyCode>,
+    pub gamepad_buttons: HashMap<PlayerAction, GamepadButtonType>,
+    pub gamepad_look_sensitivity: f32,
+    pub gamepad_deadzone: f32,
+}
+
+impl Default for Controls {
+    fn default() -> Self {
+        Self {
+            keys: HashMap::from([
+                (PlayerAction::MoveUp, KeyCode::KeyW),
+                (PlayerAction::MoveDown, KeyCode::KeyS),
+                (PlayerAction::MoveLeft, KeyCode::KeyA),
+                (PlayerAction::MoveRight, KeyCode::KeyD),
+                (PlayerAction::Sprint, KeyCode::ShiftLeft),
+                (PlayerAction::Crouch, KeyCode::ControlLeft),
+                (PlayerAction::Lighter, KeyCode::KeyF),
+                (PlayerAction::Interact, KeyCode::KeyE),
+                (PlayerAction::Pause, KeyCode::Escape),
+                (PlayerAction::QuickSave, KeyCode::F5),
+            ]),
+            gamepad_buttons: HashMap::from([
+                (PlayerAction::Sprint, GamepadButtonType::LeftThumb),
+                (PlayerAction::Crouch, GamepadButtonType::East),
+                (PlayerAction::Lighter, GamepadButtonType::West),
+                (PlayerAction::Interact, GamepadButtonType::South),
+                (PlayerAction::Pause, GamepadButtonType::Start),
+            ]),
+            gamepad_look_sensitivity: 3.0,
+            gamepad_deadzone: 0.15,
+        }
+    }
+}
+
+impl Controls {
+    /// Loads the controls file, falling back to the defaults if it is missing or broken
+    pub fn load() -> Self {
+        let Ok(contents) = fs::read_to_string(CONTROLS_PATH) else {
+            return Self::default();
+        };
+
+        let mut controls: Self = match ron::from_str(&contents) {
+            Ok(controls) => controls,
+            Err(err) => {
+                warn!("Could not read {CONTROLS_PATH}, using the default controls: {err}");
+                return Self::default();
+            }
+        };
+
+        // Actions added since the file was written get their default key, unless it is taken
+        for (action, key) in Self::default().keys {
+            if !controls.keys.contains_key(&action) && !controls.keys.values().any(|&k| k == key) {
+                controls.keys.insert(action, key);
+            }
+        }
+
+        controls
+    }
+
+    pub fn save(&self) {
+        match ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default()) {
+            Ok(contents) => {
+                if let Err(err) = fs::write(CONTROLS_PATH, contents) {
+                    error!("Could not save the controls: {err}");
+                }
+            }
+            Err(err) => error!("Could not save the controls: {err}"),
+        }
+    }
+
+    /// Binds a key to an action, the action that had the key before gets the
+    /// action's old key instead, or is left unbound
+    pub fn bind_key(&mut self, action: PlayerAction, key: KeyCode) {
+        let previous = self.keys.insert(action, key);
+        let conflicting = self
+            .keys
+            .iter()
+            .find(|&(&other, &other_key)| other != action && other_key == key)
+            .map(|(&other, _)| other);
+
+        if let Some(other) = conflicting {
+            match previous {
+                Some(previous) => self.keys.insert(other, previous),
+                None => self.keys.remove(&other),
+            };
+        }
+    }
+
+    pub fn input_map(&self) -> InputMap<PlayerAction> {
+        let mut input_map = InputMap::default()
+            .with_dual_axis(PlayerAction::MouseMotion, MouseMove::default())
+            .with_dual_axis(PlayerAction::Move, GamepadStick::LEFT)
+            .with_dual_axis(PlayerAction::Look, GamepadStick::RIGHT);
+
+        for (&action, &key) in &self.keys {
+            input_map.insert(action, key);
+        }
+
+        for (&action, &button) in &self.gamepad_buttons {
+            input_map.insert(action, button);
+        }
+
+        input_map
+    }
+
+    /// Removes the deadzone from a stick value and rescales what is left to 0..1
+    pub fn apply_deadzone(&self, value: Vec2) -> Vec2 {
+        let length = value.length();
+        if length <= self.gamepad_deadzone {
+            return Vec2::ZERO;
+        }
+
+        let scaled = ((length - self.gamepad_deadzone) / (1.0 - self.gamepad_deadzone)).min(1.0);
+        value / length * scaled
+    }
 }
@@ -1,11 +1,22 @@
 use super::{components::*, resources::*, ANGLE_EPSILON};
 use crate::{
-    game::map::{
-        components::{FloorLabel, FloorLabelUi},
-        resources::{Map, ObjectPool},
-        systems::{floor_transform, room_label_transform},
+    game::{
+        audio::{
+            components::{SoundCategory, SoundEmitter},
+            systems::{play_sound, play_sound_at},
+        },
+        enemy::components::Enemy,
+        lighting::resources::{Lighting, LightingTransition},
+        map::{
+            components::{Door, FloorLabel, FloorLabelUi},
+            resources::{FloorPoints, Map, MapSeed, ObjectPool},
+            systems::{floor_transform, room_label_transform},
+        },
+        replay::resources::Replay,
     },
+    game_over::resources::DeathReport,
     resources::{AudioAssets, MapAssets},
+    AppState,
 };
 use avian3d::prelude::*;
 use bevy::{prelude::*, window::CursorGrabMode};
@@ -14,10 +25,15 @@ use leafwing_input_manager::prelude::*;
 use rand::prelude::*;
 use std::{
     collections::HashSet,
-    f32::consts::{FRAC_PI_2, PI, TAU},
+    f32::consts::{FRAC_PI_2, FRAC_PI_4, PI, TAU},
     time::Duration,
 };
 
+/// Offset from the centre of the player's capsule to the bottom of it
+const FEET: Vec3 = Vec3::new(0.0, -0.8, 0.0);
+/// How far the player can reach with `PlayerAction::Interact`
+const INTERACT_DISTANCE: f32 = 1.5;
+
 pub fn player_spawn(mut windows: Query<&mut Window>) {
     if let Ok(mut window) = windows.get_single_mut() {
         window.cursor.visible = false;
@@ -25,30 +41,67 @@ pub fn player_spawn(mut windows: Query<&mut Window>) {
     }
 }
 
+pub fn player_despawn(mut windows: Query<&mut Window>, mut time: ResMut<Time<Virtual>>) {
+    time.unpause();
+
+    if let Ok(mut window) = windows.get_single_mut() {
+        window.cursor.visible = true;
+        window.cursor.grab_mode = CursorGrabMode::None;
+    }
+}
+
 pub fn player_input(
-    query: Query<(&ActionState<PlayerAction>, &Player)>,
+    time: Res<Time<Virtual>>,
+    controls: Res<Controls>,
+    mut query: Query<(&ActionState<PlayerAction>, &Player, &mut PlayerInput)>,
     windows: Query<&mut Window>,
-    mut input: ResMut<PlayerInput>,
+    replay: Option<Res<Replay>>,
 ) {
-    for (action_state, player) in &query {
-        if let Ok(window) = windows.get_single() {
-            if window.focused {
-                let vector = action_state.axis_pair(&PlayerAction::MouseMotion);
-                let mut delta = Vec2::ZERO;
-                delta.x += vector.x;
-                delta.y += vector.y;
-                delta *= player.mouse_sensitivity;
-
-                input.pitch = (input.pitch - delta.y)
-                    .clamp(-FRAC_PI_2 + ANGLE_EPSILON, FRAC_PI_2 - ANGLE_EPSILON);
-                input.yaw -= delta.x;
-                if input.yaw.abs() > PI {
-                    input.yaw = input.yaw.rem_euclid(TAU);
-                }
+    if time.is_paused() {
+        return;
+    }
+
+    // A replay feeds its recorded look input in whether the window is focused or not
+    let focused = replay.is_some() || windows.get_single().is_ok_and(|window| window.focused);
+
+    // A replay looks around with the settings it was recorded with
+    let replayed_controls = replay.as_ref().map(|replay| replay.file.look.controls());
+    let controls = replayed_controls.as_ref().unwrap_or(&controls);
+
+    for (action_state, player, mut input) in &mut query {
+        if focused {
+            let vector = action_state.axis_pair(&PlayerAction::MouseMotion);
+            let mut delta = Vec2::ZERO;
+            delta.x += vector.x;
+            delta.y += vector.y;
+            delta *= replay.as_ref().map_or(player.mouse_sensitivity, |replay| {
+                replay.file.look.mouse_sensitivity
+            });
+
+            let stick = controls.apply_deadzone(action_state.axis_pair(&PlayerAction::Look));
+            delta += Vec2::new(stick.x, -stick.y)
+                * controls.gamepad_look_sensitivity
+                * time.delta_seconds();
+
+            input.pitch = (input.pitch - delta.y)
+                .clamp(-FRAC_PI_2 + ANGLE_EPSILON, FRAC_PI_2 - ANGLE_EPSILON);
+            input.yaw -= delta.x;
+            if input.yaw.abs() > PI {
+                input.yaw = input.yaw.rem_euclid(TAU);
             }
         }
 
-        input.movement = Vec3::new(
+        input.gait = if action_state.pressed(&PlayerAction::Crouch) {
+            Gait::Crouch
+        } else if action_state.pressed(&PlayerAction::Sprint) {
+            Gait::Sprint
+        } else {
+            Gait::Walk
+        };
+
+        let stick = controls.apply_deadzone(action_state.axis_pair(&PlayerAction::Move));
+
+        input.movement = (Vec3::new(
             get_input_axis(
                 &PlayerAction::MoveRight,
                 &PlayerAction::MoveLeft,
@@ -56,74 +109,473 @@ pub fn player_input(
             ),
             0.0,
             get_input_axis(&PlayerAction::MoveUp, &PlayerAction::MoveDown, action_state),
-        )
-        .normalize_or_zero();
+        ) + Vec3::new(stick.x, 0.0, stick.y))
+        .clamp_length_max(1.0);
+    }
+}
+
+pub fn player_pause(
+    query: Query<&ActionState<PlayerAction>, With<Player>>,
+    mut time: ResMut<Time<Virtual>>,
+    mut windows: Query<&mut Window>,
+) {
+    for action_state in &query {
+        if !action_state.just_pressed(&PlayerAction::Pause) {
+            continue;
+        }
+
+        if time.is_paused() {
+            time.unpause();
+        } else {
+            time.pause();
+        }
+
+        if let Ok(mut window) = windows.get_single_mut() {
+            window.cursor.visible = time.is_paused();
+            window.cursor.grab_mode = if time.is_paused() {
+                CursorGrabMode::None
+            } else {
+                CursorGrabMode::Locked
+            };
+        }
     }
 }
 
+#[allow(clippy::type_complexity)]
 pub fn player_move(
-    mut query: Query<(&mut Player, &Transform, &mut LinearVelocity)>,
-    input: Res<PlayerInput>,
+    time: Res<Time>,
+    spatial_query: SpatialQuery,
+    mut query: Query<(
+        Entity,
+        &mut Player,
+        &PlayerInput,
+        &CharacterController,
+        &Collider,
+        &mut Position,
+        &mut LinearVelocity,
+        Has<Grounded>,
+    )>,
 ) {
-    for (mut player, transform, mut linear_velocity) in &mut query {
-        player.floor_index = ((-transform.translation.y - 0.5) as usize / 2) + 1;
+    let dt = time.delta_seconds();
+
+    for (
+        entity,
+        mut player,
+        input,
+        controller,
+        collider,
+        mut position,
+        mut linear_velocity,
+        grounded,
+    ) in &mut query
+    {
+        player.floor_index = FloorPoints::floor_at(position.y);
 
         let mut move_to_world = Mat3::from_axis_angle(Vec3::Y, input.yaw);
         move_to_world.z_axis *= -1.0;
 
-        let speed = 2.0;
-        let y_component = linear_velocity.0.y;
+        let velocity = move_to_world * (input.movement * player.speed * input.gait.speed_scale());
+
+        linear_velocity.x = velocity.x;
+        linear_velocity.z = velocity.z;
+
+        if !grounded {
+            linear_velocity.y -= controller.gravity * dt;
+            continue;
+        }
+
+        linear_velocity.y = 0.0;
+
+        let filter = SpatialQueryFilter::from_excluded_entities([entity]);
+        if let Some(height) = step_height(
+            &spatial_query,
+            &filter,
+            collider,
+            controller,
+            position.0,
+            velocity * dt,
+        ) {
+            position.y += height;
+        }
+    }
+}
+
+/// Height of the ledge blocking `motion`, if the controller can step onto it
+fn step_height(
+    spatial_query: &SpatialQuery,
+    filter: &SpatialQueryFilter,
+    collider: &Collider,
+    controller: &CharacterController,
+    origin: Vec3,
+    motion: Vec3,
+) -> Option<f32> {
+    let (direction, distance) = Dir3::new_and_length(motion).ok()?;
+    let cast = |origin: Vec3, direction: Dir3, distance: f32| {
+        spatial_query.cast_shape(
+            collider,
+            origin,
+            Quat::default(),
+            direction,
+            distance,
+            true,
+            filter.clone(),
+        )
+    };
+
+    // Lifted slightly so the ground the player stands on is not hit
+    let hit = cast(origin + Vec3::Y * 0.02, direction, distance)?;
+    if is_walkable(controller, -hit.normal2) {
+        return None;
+    }
+
+    let raised = origin + Vec3::Y * controller.step_height;
+    if cast(raised, direction, distance).is_some() {
+        return None;
+    }
+
+    let hit = cast(raised + motion, Dir3::NEG_Y, controller.step_height)?;
+    is_walkable(controller, -hit.normal2).then_some(controller.step_height - hit.time_of_impact)
+}
+
+fn is_walkable(controller: &CharacterController, normal: Vec3) -> bool {
+    normal.angle_between(Vec3::Y).abs() <= controller.max_slope_angle
+}
+
+/// Pushes the kinematic player out of whatever it overlaps and stops it
+/// from moving into walls, while letting it walk up walkable slopes
+#[allow(clippy::type_complexity)]
+pub fn player_collisions(
+    time: Res<Time>,
+    collisions: Res<Collisions>,
+    collider_parents: Query<&ColliderParent, Without<Sensor>>,
+    mut query: Query<
+        (
+            &mut Position,
+            &Rotation,
+            &mut LinearVelocity,
+            &CharacterController,
+        ),
+        With<Player>,
+    >,
+) {
+    for contacts in collisions.iter() {
+        let Ok([parent1, parent2]) =
+            collider_parents.get_many([contacts.entity1, contacts.entity2])
+        else {
+            continue;
+        };
+
+        let (is_first, (mut position, rotation, mut linear_velocity, controller)) =
+            if let Ok(player) = query.get_mut(parent1.get()) {
+                (true, player)
+            } else if let Ok(player) = query.get_mut(parent2.get()) {
+                (false, player)
+            } else {
+                continue;
+            };
+
+        for manifold in &contacts.manifolds {
+            let normal = if is_first {
+                -manifold.global_normal1(rotation)
+            } else {
+                -manifold.global_normal2(rotation)
+            };
+
+            let mut deepest_penetration = f32::MIN;
+            for contact in &manifold.contacts {
+                if contact.penetration > 0.0 {
+                    position.0 += normal * contact.penetration;
+                }
+                deepest_penetration = deepest_penetration.max(contact.penetration);
+            }
 
-        linear_velocity.0 = move_to_world * (input.movement * speed);
-        linear_velocity.y = y_component;
+            let slope_angle = normal.angle_between(Vec3::Y);
+            let climbable = slope_angle.abs() <= controller.max_slope_angle;
+
+            if deepest_penetration > 0.0 {
+                if climbable {
+                    // Climb the slope instead of being pushed back down it
+                    let normal_xz = normal.reject_from_normalized(Vec3::Y).normalize_or_zero();
+                    let max_y_speed = -linear_velocity.dot(normal_xz) * slope_angle.tan();
+                    linear_velocity.y = linear_velocity.y.max(max_y_speed);
+                } else if linear_velocity.dot(normal) < 0.0 {
+                    // Slide along the wall without bouncing off it
+                    linear_velocity.0 = linear_velocity.reject_from_normalized(normal);
+                }
+            } else {
+                // Speculative contact, only remove the speed that would close the gap
+                let normal_speed = linear_velocity.dot(normal);
+                if normal_speed > 0.0 {
+                    continue;
+                }
+
+                let mut impulse =
+                    (normal_speed - deepest_penetration / time.delta_seconds()) * normal;
+                if climbable {
+                    linear_velocity.y -= impulse.y.min(0.0);
+                } else {
+                    impulse.y = impulse.y.max(0.0);
+                    linear_velocity.0 -= impulse;
+                }
+            }
+        }
     }
 }
 
 pub fn player_look(
     time: Res<Time>,
-    q_player: Query<(&Transform, &LinearVelocity, &Player), Without<PlayerCamera>>,
+    q_player: Query<(&Transform, &LinearVelocity, &Player, &PlayerInput), Without<PlayerCamera>>,
     mut q_camera: Query<(&mut PlayerCamera, &mut Transform), Without<Player>>,
-    input: Res<PlayerInput>,
 ) {
     let dt = time.delta_seconds();
 
-    for (p_transform, linear_velocity, player) in &q_player {
+    for (p_transform, linear_velocity, player, input) in &q_player {
         for (mut camera, mut c_transform) in &mut q_camera {
             camera.timer += dt * linear_velocity.length() / player.speed;
 
+            let crouching = if input.gait == Gait::Crouch { 1.0 } else { 0.0 };
+            camera.crouch = camera.crouch.lerp(crouching, (dt * 8.0).min(1.0));
+
+            let bob_scale = camera.bob_scale(input.gait);
             let c_off = Vec3::new(
                 (camera.timer * camera.speed / 2.0).cos(),
                 -(camera.timer * camera.speed).sin(),
                 0.0,
             );
 
-            let rot = -(camera.timer * camera.speed / 2.0).cos() * camera.tilt;
+            let rot = -(camera.timer * camera.speed / 2.0).cos() * camera.tilt * bob_scale;
 
-            c_transform.translation =
-                p_transform.translation + player.camera_height + c_off * camera.max_bob;
+            c_transform.translation = p_transform.translation + player.camera_height
+                - Vec3::Y * camera.crouch_height * camera.crouch
+                + c_off * camera.max_bob * bob_scale;
             c_transform.rotation = Quat::from_euler(EulerRot::YXZ, input.yaw, input.pitch, 0.0)
                 * Quat::from_rotation_z(rot);
         }
     }
 }
 
+pub fn player_shake(
+    time: Res<Time>,
+    mut commands: Commands,
+    mut p_query: Query<&mut LinearVelocity, With<Player>>,
+    mut c_query: Query<(Entity, &mut CameraShake, &mut Transform), With<PlayerCamera>>,
+    mut rng: ResMut<GlobalEntropy<WyRand>>,
+) {
+    let dt = time.delta_seconds();
+
+    for (entity, mut shake, mut c_transform) in &mut c_query {
+        if shake.timer.tick(time.delta()).finished() {
+            commands.entity(entity).remove::<CameraShake>();
+            continue;
+        }
+
+        for mut linear_velocity in &mut p_query {
+            linear_velocity.0 += Vec3::new(
+                rng.gen_range(-0.3..0.3),
+                rng.gen_range(-0.3..0.3),
+                rng.gen_range(-0.3..0.3),
+            ) * dt;
+        }
+
+        c_transform.rotate(Quat::from_euler(
+            EulerRot::XYZ,
+            f32::to_radians(rng.gen_range(-1.0..1.0)),
+            f32::to_radians(rng.gen_range(-1.0..1.0)),
+            f32::to_radians(rng.gen_range(-1.0..1.0)),
+        ));
+    }
+}
+
+pub fn player_stamina(
+    time: Res<Time>,
+    mut commands: Commands,
+    audio_assets: Res<AudioAssets>,
+    mut p_query: Query<(&mut Stamina, &mut PlayerInput, &LinearVelocity), With<Player>>,
+    c_query: Query<Entity, With<PlayerCamera>>,
+) {
+    let dt = time.delta_seconds();
+
+    for (mut stamina, mut input, linear_velocity) in &mut p_query {
+        if stamina.exhausted && input.gait == Gait::Sprint {
+            input.gait = Gait::Walk;
+        }
+
+        let sprinting = input.gait == Gait::Sprint && linear_velocity.xz().length() > 0.1;
+        stamina.current = if sprinting {
+            (stamina.current - stamina.drain * dt).max(0.0)
+        } else {
+            (stamina.current + stamina.recovery * dt).min(1.0)
+        };
+
+        if stamina.current <= 0.0 {
+            stamina.exhausted = true;
+        } else if stamina.current >= stamina.breathless {
+            stamina.exhausted = false;
+        }
+
+        if stamina.current >= stamina.breathless {
+            // Primed so the first breath comes as soon as the player runs out
+            let duration = stamina.breath_timer.duration();
+            stamina.breath_timer.set_elapsed(duration);
+            continue;
+        }
+
+        if stamina.breath_timer.tick(time.delta()).just_finished() {
+            // Breathing comes from just below the camera
+            for entity in &c_query {
+                commands.entity(entity).with_children(|parent| {
+                    parent.spawn((
+                        AudioBundle {
+                            source: audio_assets.breath_sfx.clone(),
+                            settings: PlaybackSettings::DESPAWN.with_spatial(true),
+                        },
+                        TransformBundle::from_transform(Transform::from_xyz(0.0, -0.1, -0.1)),
+                        SoundCategory::Effect,
+                        SoundEmitter::default(),
+                    ));
+                });
+            }
+        }
+    }
+}
+
+/// Knocks on the locked door the player is facing
+pub fn player_interact(
+    mut commands: Commands,
+    audio_assets: Res<AudioAssets>,
+    spatial_query: SpatialQuery,
+    p_query: Query<(Entity, &ActionState<PlayerAction>), With<Player>>,
+    c_query: Query<&GlobalTransform, With<PlayerCamera>>,
+    d_query: Query<(), With<Door>>,
+) {
+    let (Ok((player, action_state)), Ok(c_transform)) =
+        (p_query.get_single(), c_query.get_single())
+    else {
+        return;
+    };
+
+    if !action_state.just_pressed(&PlayerAction::Interact) {
+        return;
+    }
+
+    let origin = c_transform.translation();
+    let Some(hit) = spatial_query.cast_ray(
+        origin,
+        c_transform.forward(),
+        INTERACT_DISTANCE,
+        true,
+        SpatialQueryFilter::from_excluded_entities([player]),
+    ) else {
+        return;
+    };
+
+    if d_query.contains(hit.entity) {
+        play_sound_at(
+            &mut commands,
+            SoundCategory::Effect,
+            audio_assets.loud_step_sound.clone(),
+            origin + c_transform.forward() * hit.time_of_impact,
+        );
+    }
+}
+
+pub fn player_lighter(
+    time: Res<Time>,
+    mut commands: Commands,
+    audio_assets: Res<AudioAssets>,
+    p_query: Query<&ActionState<PlayerAction>, With<Player>>,
+    mut l_query: Query<(&mut Lighter, &mut PointLight)>,
+    mut rng: ResMut<GlobalEntropy<WyRand>>,
+) {
+    let dt = time.delta_seconds();
+    let toggled = p_query
+        .iter()
+        .any(|action_state| action_state.just_pressed(&PlayerAction::Lighter));
+
+    for (mut lighter, mut point_light) in &mut l_query {
+        let was_lit = lighter.lit;
+
+        if toggled {
+            if lighter.lit {
+                lighter.extinguish();
+            } else if lighter.fuel > 0.0 {
+                lighter.lit = true;
+            }
+        }
+
+        if lighter.lit {
+            lighter.fuel = (lighter.fuel - dt).max(0.0);
+            if lighter.fuel <= 0.0 {
+                lighter.extinguish();
+            }
+        }
+
+        if lighter.lit != was_lit {
+            let source = if lighter.lit {
+                &audio_assets.fire_on
+            } else {
+                &audio_assets.fire_off
+            };
+
+            play_sound(&mut commands, SoundCategory::Effect, source.clone());
+        }
+
+        if !lighter.lit {
+            point_light.intensity = 0.0;
+            continue;
+        }
+
+        let target = rng.gen_range(0.75..1.0);
+        lighter.flicker = lighter.flicker.lerp(target, (dt * 12.0).min(1.0));
+
+        // Smothered down to a tenth of the flame, it never quite goes out
+        let starve = lighter
+            .starve
+            .as_mut()
+            .map_or(1.0, |timer| 1.0 - timer.tick(time.delta()).fraction() * 0.9);
+
+        point_light.intensity = lighter.intensity * lighter.flicker * starve;
+    }
+}
+
+#[allow(clippy::type_complexity)]
 pub fn player_footsteps(
     time: Res<Time>,
     mut commands: Commands,
     audio_assets: Res<AudioAssets>,
-    mut query: Query<(Entity, &LinearVelocity, &mut PlayerFootsteps, &Transform), With<Player>>,
+    mut query: Query<
+        (
+            &Transform,
+            &LinearVelocity,
+            &PlayerInput,
+            &mut PlayerFootsteps,
+        ),
+        (With<Player>, With<Grounded>),
+    >,
 ) {
     let dt = time.delta_seconds();
 
-    for (entity, linear_velocity, mut footsteps, _transform) in &mut query {
+    for (transform, linear_velocity, input, mut footsteps) in &mut query {
         footsteps
             .timer
             .tick(Duration::from_secs_f32(dt * linear_velocity.length()));
 
         if footsteps.timer.finished() {
-            commands.entity(entity).insert(AudioBundle {
-                source: audio_assets.step_sound.clone(),
-                settings: PlaybackSettings::REMOVE,
+            let (source, volume) = match input.gait {
+                Gait::Walk => (&audio_assets.step_sound, 1.0),
+                Gait::Sprint => (&audio_assets.loud_step_sound, 1.0),
+                Gait::Crouch => (&audio_assets.step_sound, 0.3),
+            };
+
+            play_sound_at(
+                &mut commands,
+                SoundCategory::Footsteps,
+                source.clone(),
+                transform.translation + FEET,
+            )
+            .insert(SoundEmitter {
+                volume,
+                ..default()
             });
         }
     }
@@ -190,66 +642,212 @@ pub fn player_label_floor(
     }
 }
 
+pub fn player_enemy_contact(
+    mut events: EventReader<CollisionStarted>,
+    mut p_query: Query<(&mut Player, &Transform)>,
+    e_query: Query<(&Enemy, &Transform)>,
+) {
+    for CollisionStarted(a, b) in events.read() {
+        let player = if e_query.contains(*b) {
+            *a
+        } else if e_query.contains(*a) {
+            *b
+        } else {
+            continue;
+        };
+
+        if let Ok((mut player, _)) = p_query.get_mut(player) {
+            player.kill_timer = player.kill_timer.max(1.0);
+        }
+    }
+
+    for (mut player, p_transform) in &mut p_query {
+        let caught = e_query.iter().any(|(enemy, e_transform)| {
+            e_transform.translation.distance(p_transform.translation) < enemy.kill_radius
+        });
+
+        if caught {
+            player.kill_timer = player.kill_timer.max(1.0);
+        }
+    }
+}
+
 pub fn player_death(
     time: Res<Time>,
-    mut query: Query<&mut Player>,
     mut commands: Commands,
     audio_assets: Res<AudioAssets>,
-    mut rng: ResMut<GlobalEntropy<WyRand>>,
-    // mut ambient_light: ResMut<AmbientLight>,
+    mut lighting: ResMut<Lighting>,
+    mut query: Query<(Entity, &mut Player), Without<Dying>>,
 ) {
-    let dt = time.delta_seconds();
-
-    if let Ok(mut player) = query.get_single_mut() {
+    for (entity, mut player) in &mut query {
         if player.kill_timer > 0.0 {
-            if player.kill_timer == 1.0 {
-                commands.spawn(AudioBundle {
-                    source: audio_assets.death_sfx.clone(),
-                    settings: PlaybackSettings::REMOVE,
-                });
-            }
+            let dying = Dying::default();
 
-            player.kill_timer += 1.0 * dt;
+            // Fade to red
+            lighting.replace(LightingTransition {
+                color: Some([1.0, 0.0, 0.0]),
+                duration: dying.timer.duration().as_secs_f32(),
+                ..default()
+            });
+
+            commands.entity(entity).insert(dying);
+            // Not state scoped, so it carries on over the death screen
+            commands.spawn((
+                AudioBundle {
+                    source: audio_assets.death_sfx.clone(),
+                    settings: PlaybackSettings::DESPAWN,
+                },
+                SoundCategory::Scare,
+            ));
+        } else {
+            player.time_alive += time.delta_seconds();
+        }
+    }
+}
 
-            // let secs = player.kill_timer.elapsed().as_secs_f32();
-            // ambient_light.color = Color::srgb_from_array([255.0 - secs, 100.0 - secs, 100.0 - secs]);
-            // RotateEntity camera, -KillTimer, EntityYaw(camera), EntityRoll(collider)-(KillTimer/2)
+pub fn player_dying(
+    time: Res<Time>,
+    mut commands: Commands,
+    seed: Res<MapSeed>,
+    mut rng: ResMut<GlobalEntropy<WyRand>>,
+    mut next_app_state: ResMut<NextState<AppState>>,
+    mut p_query: Query<(&Player, &PlayerInput, &mut Dying, &mut LinearVelocity)>,
+    mut c_query: Query<&mut Transform, With<PlayerCamera>>,
+) {
+    for (player, input, mut dying, mut linear_velocity) in &mut p_query {
+        let t = dying.timer.tick(time.delta()).fraction();
+
+        linear_velocity.x = 0.0;
+        linear_velocity.z = 0.0;
+
+        // Fall onto the back, rolling to the side
+        for mut c_transform in &mut c_query {
+            c_transform.translation -= player.camera_height * t;
+            c_transform.rotation = Quat::from_euler(
+                EulerRot::YXZ,
+                input.yaw,
+                input.pitch.lerp(FRAC_PI_2 - ANGLE_EPSILON, t),
+                -FRAC_PI_4 * t,
+            );
+        }
 
-            if player.kill_timer > 90.0 && player.floor_index > 130 {
+        if dying.timer.finished() {
+            let message = (player.floor_index > 130).then(|| {
                 match rng.gen_range(1..7) {
-                    2 => panic!("It's not about whether you die or not, it's about when you die."),
-                    3 => panic!("NICE"),
-                    4 => panic!("welcome to NIL"),
-                    _ => panic!("NO"),
+                    2 => "It's not about whether you die or not, it's about when you die.",
+                    3 => "NICE",
+                    4 => "welcome to NIL",
+                    _ => "NO",
                 }
-            }
+                .to_string()
+            });
+
+            commands.insert_resource(DeathReport {
+                floor: player.floor_index,
+                seed: seed.0,
+                time_alive: player.time_alive,
+                message,
+            });
+            next_app_state.set(AppState::GameOver);
         }
     }
 }
 
-pub fn player_ambience(
+#[allow(clippy::type_complexity)]
+pub fn player_ground(
     mut commands: Commands,
-    audio_assets: Res<AudioAssets>,
-    mut rng: ResMut<GlobalEntropy<WyRand>>,
+    spatial_query: SpatialQuery,
+    mut query: Query<
+        (
+            Entity,
+            &ShapeHits,
+            &ShapeCaster,
+            &Rotation,
+            &CharacterController,
+            &LinearVelocity,
+            &mut Position,
+            Has<Grounded>,
+        ),
+        With<Player>,
+    >,
 ) {
-    if rng.gen_range(1..1000) < 2 {
-        // TODO: Make 3d audio.
-        commands.spawn(AudioBundle {
-            source: audio_assets.ambient_sfx[rng.gen_range(0..8)].clone(),
-            settings: PlaybackSettings::REMOVE,
-        });
+    for (entity, hits, caster, rotation, controller, linear_velocity, mut position, grounded) in
+        &mut query
+    {
+        let on_ground = hits
+            .iter()
+            .any(|hit| is_walkable(controller, rotation * -hit.normal2));
+
+        if on_ground == grounded {
+            continue;
+        }
+
+        if on_ground {
+            commands.entity(entity).insert(Grounded);
+            continue;
+        }
+
+        // Walking off a ledge or down the stairs, stick to the ground below
+        if linear_velocity.y <= 0.0 {
+            let snap = spatial_query.cast_shape(
+                &caster.shape,
+                position.0 + caster.origin,
+                caster.shape_rotation,
+                Dir3::NEG_Y,
+                controller.snap_distance,
+                true,
+                SpatialQueryFilter::from_excluded_entities([entity]),
+            );
+
+            if let Some(hit) = snap.filter(|hit| is_walkable(controller, -hit.normal2)) {
+                position.y -= hit.time_of_impact;
+                continue;
+            }
+        }
+
+        commands.entity(entity).remove::<Grounded>();
     }
 }
 
-// pub fn player_fall_damage(mut query: Query<(&mut Player, &LinearVelocity)>) {
-//     for (mut player, linear_velocity) in &mut query {
-//         info!("Player Y Velocity: {:#?}", linear_velocity.y);
+pub fn player_fall_damage(
+    mut commands: Commands,
+    audio_assets: Res<AudioAssets>,
+    mut p_query: Query<(&mut Player, &mut PlayerFall, &Transform, Has<Grounded>)>,
+    c_query: Query<Entity, With<PlayerCamera>>,
+) {
+    for (mut player, mut fall, transform, grounded) in &mut p_query {
+        let y = transform.translation.y;
+
+        if !grounded {
+            fall.peak = Some(fall.peak.map_or(y, |peak| peak.max(y)));
+            continue;
+        }
+
+        let Some(peak) = fall.peak.take() else {
+            continue;
+        };
+        let height = peak - y;
+
+        if height >= fall.lethal {
+            player.kill_timer = player.kill_timer.max(1.0);
+        }
+
+        if height >= fall.hard_landing {
+            play_sound_at(
+                &mut commands,
+                SoundCategory::Footsteps,
+                audio_assets.loud_step_sound.clone(),
+                transform.translation + FEET,
+            );
 
-//         if linear_velocity.y < -0.09 {
-//             player.kill_timer = player.kill_timer.max(1.0);
-//         }
-//     }
-// }
+            for entity in &c_query {
+                commands.entity(entity).insert(CameraShake {
+                    timer: Timer::from_seconds(0.3, TimerMode::Once),
+                });
+            }
+        }
+    }
+}
 
 fn get_input_axis<A: Actionlike>(paction: &A, saction: &A, action_state: &ActionState<A>) -> f32 {
     get_input_value(paction, action_state) - get_input_value(saction, action_state)
@@ -0,0 +1,55 @@
+use super::systems::{spawn_map, spawn_player};
+use crate::AppState;
+use bevy::{app::AppExit, prelude::*};
+use leafwing_input_manager::plugin::InputManagerSystem;
+use resources::{arg_value, Recorder, Replay, ReplayFile};
+use systems::*;
+
+pub mod resources;
+mod systems;
+
+/// Records runs with `--record <path>` and plays them back with `--replay <path>`
+pub struct ReplayPlugin;
+
+impl Plugin for ReplayPlugin {
+    fn build(&self, app: &mut App) {
+        if let Some(path) = arg_value("--replay") {
+            match ReplayFile::load(&path) {
+                Ok(file) => {
+                    app.insert_resource(Replay { file, frame: 0 });
+                }
+                Err(err) => error!("Could not load the replay {path}: {err}"),
+            }
+        } else if let Some(path) = arg_value("--record") {
+            app.insert_resource(Recorder {
+                path,
+                frames: Vec::new(),
+            });
+        }
+
+        app.add_systems(
+            OnEnter(AppState::MainMenu),
+            replay_skip_menu.run_if(resource_exists::<Replay>),
+        )
+        .add_systems(
+            OnEnter(AppState::Game),
+            (
+                replay_seed
+                    .before(spawn_map)
+                    .run_if(resource_exists::<Replay>.or_else(resource_exists::<Recorder>)),
+                replay_start.after(spawn_player),
+            ),
+        )
+        .add_systems(
+            PreUpdate,
+            (
+                replay_frames.run_if(resource_exists::<Replay>),
+                record_frames.run_if(resource_exists::<Recorder>),
+            )
+                .in_set(InputManagerSystem::ManualControl)
+                .run_if(in_state(AppState::Game)),
+        )
+        .add_systems(OnExit(AppState::Game), save_recording)
+        .add_systems(Last, save_recording.run_if(on_event::<AppExit>()));
+    }
+}
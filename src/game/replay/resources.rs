@@ -0,0 +1,122 @@
+use crate::{
+    game::player::resources::{Controls, PlayerAction},
+    settings::Settings,
+};
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::{fs, path::Path};
+use thiserror::Error;
+
+pub const REPLAY_VERSION: u32 = 2;
+
+/// Inputs of a single frame, captured from the player's `ActionState`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ReplayFrame {
+    pub delta: f32,
+    pub pressed: Vec<PlayerAction>,
+    pub mouse_motion: Vec2,
+    pub movement: Vec2,
+    pub look: Vec2,
+}
+
+/// Look settings of the recorded run, used on playback instead of the local ones
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct ReplayLook {
+    pub mouse_sensitivity: f32,
+    pub gamepad_look_sensitivity: f32,
+    pub gamepad_deadzone: f32,
+}
+
+impl ReplayLook {
+    pub fn new(settings: &Settings, controls: &Controls) -> Self {
+        Self {
+            mouse_sensitivity: settings.mouse_sensitivity,
+            gamepad_look_sensitivity: controls.gamepad_look_sensitivity,
+            gamepad_deadzone: controls.gamepad_deadzone,
+        }
+    }
+
+    /// Controls with the recorded gamepad settings and no bindings
+    pub fn controls(&self) -> Controls {
+        Controls {
+            keys: default(),
+            gamepad_buttons: default(),
+            gamepad_look_sensitivity: self.gamepad_look_sensitivity,
+            gamepad_deadzone: self.gamepad_deadzone,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ReplayFile {
+    pub version: u32,
+    pub seed: u64,
+    pub look: ReplayLook,
+    pub frames: Vec<ReplayFrame>,
+}
+
+#[derive(Deserialize)]
+struct ReplayHeader {
+    version: u32,
+}
+
+#[derive(Error, Debug)]
+pub enum ReplayError {
+    #[error("could not access the replay file: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("the replay file is corrupted: {0}")]
+    Corrupted(#[from] ron::error::SpannedError),
+    #[error("could not write the replay file: {0}")]
+    Serialize(#[from] ron::Error),
+    #[error(
+        "the replay file has version {found}, but only version {} is supported",
+        REPLAY_VERSION
+    )]
+    UnsupportedVersion { found: u32 },
+}
+
+impl ReplayFile {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ReplayError> {
+        let contents = fs::read_to_string(path)?;
+
+        let header: ReplayHeader = ron::from_str(&contents)?;
+        if header.version != REPLAY_VERSION {
+            return Err(ReplayError::UnsupportedVersion {
+                found: header.version,
+            });
+        }
+
+        Ok(ron::from_str(&contents)?)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), ReplayError> {
+        fs::write(path, ron::to_string(self)?)?;
+        Ok(())
+    }
+}
+
+/// Captures every frame of the current run, written out when the run ends
+#[derive(Resource)]
+pub struct Recorder {
+    pub path: String,
+    pub frames: Vec<ReplayFrame>,
+}
+
+/// Feeds a recorded run back into the player's `ActionState`
+#[derive(Resource)]
+pub struct Replay {
+    pub file: ReplayFile,
+    pub frame: usize,
+}
+
+/// Whether an action is recorded and replayed, a replayed quicksave would overwrite the save
+pub fn is_replayed(action: &PlayerAction) -> bool {
+    *action != PlayerAction::QuickSave
+}
+
+/// Reads the value following `flag` on the command line
+pub fn arg_value(flag: &str) -> Option<String> {
+    let mut args = std::env::args().skip(1);
+    args.find(|arg| arg == flag)?;
+    args.next()
+}
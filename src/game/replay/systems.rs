@@ -0,0 +1,311 @@
+use super::resources::{
+    is_replayed, Recorder, Replay, ReplayFile, ReplayFrame, ReplayLook, REPLAY_VERSION,
+};
+use crate::{
+    game::{
+        map::resources::MapSeed,
+        player::{
+            components::Player,
+            resources::{Controls, PlayerAction},
+        },
+    },
+    settings::Settings,
+    AppState,
+};
+use bevy::{prelude::*, time::TimeUpdateStrategy};
+use bevy_rand::prelude::*;
+use leafwing_input_manager::prelude::*;
+use std::time::Duration;
+
+pub fn replay_skip_menu(mut next_app_state: ResMut<NextState<AppState>>) {
+    next_app_state.set(AppState::Game);
+}
+
+/// Seeds the map and the global rng from the replay, recordings seed the rng from the map seed
+/// so the scares, sounds and glimpses play out the same way again
+pub fn replay_seed(
+    replay: Option<Res<Replay>>,
+    mut seed: ResMut<MapSeed>,
+    mut rng: ResMut<GlobalEntropy<WyRand>>,
+) {
+    if let Some(replay) = replay {
+        seed.0 = replay.file.seed;
+    }
+
+    rng.reseed(seed.0.to_le_bytes());
+}
+
+pub fn replay_start(
+    mut commands: Commands,
+    replay: Option<Res<Replay>>,
+    query: Query<Entity, With<Player>>,
+) {
+    let Some(replay) = replay else {
+        return;
+    };
+
+    // Live input would mix with the recorded one
+    for entity in &query {
+        commands
+            .entity(entity)
+            .insert(InputMap::<PlayerAction>::default());
+    }
+
+    if let Some(frame) = replay.file.frames.first() {
+        commands.insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f32(
+            frame.delta,
+        )));
+    }
+}
+
+pub fn replay_frames(
+    mut commands: Commands,
+    mut replay: ResMut<Replay>,
+    mut query: Query<&mut ActionState<PlayerAction>, With<Player>>,
+) {
+    let Some(frame) = replay.file.frames.get(replay.frame).cloned() else {
+        info!("Replay finished after {} frames", replay.frame);
+        commands.remove_resource::<Replay>();
+        commands.insert_resource(TimeUpdateStrategy::Automatic);
+        return;
+    };
+
+    for mut action_state in &mut query {
+        for action in action_state.get_pressed() {
+            if !frame.pressed.contains(&action) {
+                action_state.release(&action);
+            }
+        }
+
+        for action in frame.pressed.iter().filter(|action| is_replayed(action)) {
+            if !action_state.pressed(action) {
+                action_state.press(action);
+            }
+        }
+
+        action_state.set_axis_pair(&PlayerAction::MouseMotion, frame.mouse_motion);
+        action_state.set_axis_pair(&PlayerAction::Move, frame.movement);
+        action_state.set_axis_pair(&PlayerAction::Look, frame.look);
+    }
+
+    // Time is advanced before the inputs are fed, so the next frame's delta is queued up now
+    replay.frame += 1;
+    if let Some(next) = replay.file.frames.get(replay.frame) {
+        commands.insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f32(
+            next.delta,
+        )));
+    }
+}
+
+pub fn record_frames(
+    time: Res<Time>,
+    mut recorder: ResMut<Recorder>,
+    query: Query<&ActionState<PlayerAction>, With<Player>>,
+) {
+    if let Ok(action_state) = query.get_single() {
+        recorder.frames.push(ReplayFrame {
+            delta: time.delta_seconds(),
+            pressed: action_state
+                .get_pressed()
+                .into_iter()
+                .filter(is_replayed)
+                .collect(),
+            mouse_motion: action_state.axis_pair(&PlayerAction::MouseMotion),
+            movement: action_state.axis_pair(&PlayerAction::Move),
+            look: action_state.axis_pair(&PlayerAction::Look),
+        });
+    }
+}
+
+pub fn save_recording(
+    seed: Res<MapSeed>,
+    settings: Res<Settings>,
+    controls: Res<Controls>,
+    recorder: Option<ResMut<Recorder>>,
+) {
+    let Some(mut recorder) = recorder else {
+        return;
+    };
+
+    if recorder.frames.is_empty() {
+        return;
+    }
+
+    let file = ReplayFile {
+        version: REPLAY_VERSION,
+        seed: seed.0,
+        look: ReplayLook::new(&settings, &controls),
+        frames: std::mem::take(&mut recorder.frames),
+    };
+
+    match file.save(&recorder.path) {
+        Ok(()) => info!("Recorded {} frames to {}", file.frames.len(), recorder.path),
+        Err(err) => error!("Could not save the recording: {err}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{
+        player::{components::PlayerInput, resources::Controls, systems::player_input},
+        testing::{test_app, FRAME},
+    };
+    use rand::Rng;
+
+    /// Inputs of a short run: walking, sprinting and looking around
+    fn inputs(frame: usize) -> (Vec<PlayerAction>, Vec2) {
+        let pressed = match frame {
+            0..=2 => vec![PlayerAction::MoveUp],
+            3 => vec![PlayerAction::MoveUp, PlayerAction::QuickSave],
+            4 => vec![PlayerAction::MoveUp],
+            5..=9 => vec![PlayerAction::MoveUp, PlayerAction::Sprint],
+            _ => vec![PlayerAction::Crouch],
+        };
+        (pressed, Vec2::new(frame as f32 * 0.1, -0.05))
+    }
+
+    fn spawn_player(app: &mut App) -> Entity {
+        app.world_mut()
+            .spawn((
+                Player::default(),
+                PlayerInput::default(),
+                ActionState::<PlayerAction>::default(),
+            ))
+            .id()
+    }
+
+    fn record(frames: usize) -> ReplayFile {
+        let mut app = test_app();
+        app.insert_resource(Recorder {
+            path: String::new(),
+            frames: Vec::new(),
+        })
+        .add_systems(Update, record_frames);
+        let player = spawn_player(&mut app);
+
+        for frame in 0..frames {
+            let (pressed, look) = inputs(frame);
+            let mut action_state = app
+                .world_mut()
+                .get_mut::<ActionState<PlayerAction>>(player)
+                .unwrap();
+            for action in action_state.get_pressed() {
+                action_state.release(&action);
+            }
+            for action in &pressed {
+                action_state.press(action);
+            }
+            action_state.set_axis_pair(&PlayerAction::MouseMotion, look);
+
+            app.update();
+        }
+
+        ReplayFile {
+            version: REPLAY_VERSION,
+            seed: 87,
+            // Not the local settings, so the replay has to use these
+            look: ReplayLook {
+                mouse_sensitivity: 0.005,
+                gamepad_look_sensitivity: 2.0,
+                gamepad_deadzone: 0.25,
+            },
+            frames: app.world_mut().resource_mut::<Recorder>().frames.clone(),
+        }
+    }
+
+    #[test]
+    fn recording_round_trips_through_a_file() {
+        let path = std::env::temp_dir().join(format!("scp087b_replay_{}.ron", std::process::id()));
+        let file = record(15);
+        assert_eq!(file.frames.len(), 15);
+        assert!(!file.frames[3].pressed.contains(&PlayerAction::QuickSave));
+        assert!((file.frames[14].delta - FRAME).abs() < 1e-6);
+
+        file.save(&path).unwrap();
+        let loaded = ReplayFile::load(&path);
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded.unwrap(), file);
+    }
+
+    #[test]
+    fn replay_feeds_the_recorded_inputs_back() {
+        let mut file = record(15);
+        file.frames[3].pressed.push(PlayerAction::QuickSave);
+        for frame in &mut file.frames {
+            // Within the recorded deadzone, but not the local one
+            frame.look = Vec2::new(0.2, 0.0);
+        }
+
+        let mut app = test_app();
+        app.init_resource::<Controls>()
+            .insert_resource(Replay {
+                file: file.clone(),
+                frame: 0,
+            })
+            .add_systems(
+                Update,
+                (
+                    replay_frames.run_if(resource_exists::<Replay>),
+                    player_input,
+                )
+                    .chain(),
+            );
+        // No window at all, so it is never focused
+        let player = spawn_player(&mut app);
+
+        let mut yaw = 0.0;
+        for (i, frame) in file.frames.iter().enumerate() {
+            app.update();
+
+            let world = app.world();
+            let action_state = world.get::<ActionState<PlayerAction>>(player).unwrap();
+            let (pressed, look) = inputs(i);
+            let mut replayed = action_state.get_pressed();
+            replayed.sort_by_key(|action| format!("{action:?}"));
+            let mut expected: Vec<_> = pressed.into_iter().filter(is_replayed).collect();
+            expected.sort_by_key(|action| format!("{action:?}"));
+            assert_eq!(replayed, expected);
+            assert_eq!(action_state.axis_pair(&PlayerAction::MouseMotion), look);
+            assert_eq!(frame.mouse_motion, look);
+
+            yaw -= look.x * file.look.mouse_sensitivity;
+            let input = world.get::<PlayerInput>(player).unwrap();
+            assert!((input.yaw - yaw).abs() < 1e-4);
+        }
+
+        app.update();
+        assert!(!app.world().contains_resource::<Replay>());
+    }
+
+    #[test]
+    fn replay_seeds_the_global_rng() {
+        let draws = |skip: usize| {
+            let mut app = test_app();
+            app.init_resource::<MapSeed>()
+                .insert_resource(Replay {
+                    file: ReplayFile {
+                        version: REPLAY_VERSION,
+                        seed: 87,
+                        look: ReplayLook::new(&Settings::default(), &Controls::default()),
+                        frames: Vec::new(),
+                    },
+                    frame: 0,
+                })
+                .add_systems(Update, replay_seed);
+            // Throws the rng off its initial state first
+            let mut rng = app.world_mut().resource_mut::<GlobalEntropy<WyRand>>();
+            for _ in 0..skip {
+                rng.gen::<u64>();
+            }
+            app.update();
+
+            assert_eq!(app.world().resource::<MapSeed>().0, 87);
+            let mut rng = app.world_mut().resource_mut::<GlobalEntropy<WyRand>>();
+            [rng.gen::<u64>(), rng.gen::<u64>()]
+        };
+
+        assert_eq!(draws(0), draws(3));
+    }
+}
@@ -0,0 +1,35 @@
+use super::systems::{spawn_map, spawn_player};
+use crate::AppState;
+use bevy::prelude::*;
+use resources::SaveGame;
+use systems::*;
+
+pub mod resources;
+mod systems;
+
+pub struct SavePlugin;
+
+impl Plugin for SavePlugin {
+    fn build(&self, app: &mut App) {
+        match SaveGame::from_args() {
+            Some(Ok(save)) => {
+                app.insert_resource(save);
+            }
+            Some(Err(err)) => error!("Could not continue the saved game: {err}"),
+            None => {}
+        }
+
+        app.add_systems(
+            OnEnter(AppState::MainMenu),
+            save_skip_menu.run_if(resource_exists::<SaveGame>),
+        )
+        .add_systems(
+            OnEnter(AppState::Game),
+            (
+                load_save_seed.before(spawn_map),
+                apply_save.after(spawn_player),
+            ),
+        )
+        .add_systems(Update, quicksave.run_if(in_state(AppState::Game)));
+    }
+}
@@ -0,0 +1,164 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::{fs, path::Path};
+use thiserror::Error;
+
+pub const SAVE_VERSION: u32 = 1;
+pub const SAVE_PATH: &str = "save.ron";
+
+/// Everything needed to resume a descent, the map itself is regenerated
+/// from the seed
+#[derive(Resource, Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct SaveGame {
+    pub version: u32,
+    pub seed: u64,
+    pub floors: Vec<FloorState>,
+    pub position: Vec3,
+    pub yaw: f32,
+    pub pitch: f32,
+    pub kill_timer: f32,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct FloorState {
+    pub stage: Option<usize>,
+    pub elapsed: f32,
+}
+
+#[derive(Deserialize)]
+struct SaveHeader {
+    version: u32,
+}
+
+#[derive(Error, Debug)]
+pub enum SaveError {
+    #[error("could not access the save file: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("the save file is corrupted: {0}")]
+    Corrupted(#[from] ron::error::SpannedError),
+    #[error("could not write the save file: {0}")]
+    Serialize(#[from] ron::Error),
+    #[error(
+        "the save file has version {found}, but only version {} is supported",
+        SAVE_VERSION
+    )]
+    UnsupportedVersion { found: u32 },
+}
+
+impl SaveGame {
+    pub fn parse(contents: &str) -> Result<Self, SaveError> {
+        let header: SaveHeader = ron::from_str(contents)?;
+        if header.version != SAVE_VERSION {
+            return Err(SaveError::UnsupportedVersion {
+                found: header.version,
+            });
+        }
+
+        Ok(ron::from_str(contents)?)
+    }
+
+    pub fn to_ron(&self) -> Result<String, SaveError> {
+        Ok(ron::ser::to_string_pretty(
+            self,
+            ron::ser::PrettyConfig::default(),
+        )?)
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, SaveError> {
+        Self::parse(&fs::read_to_string(path)?)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), SaveError> {
+        fs::write(path, self.to_ron()?)?;
+        Ok(())
+    }
+
+    /// Loads the save file if the game was started with `--continue`
+    pub fn from_args() -> Option<Result<Self, SaveError>> {
+        std::env::args()
+            .skip(1)
+            .any(|arg| arg == "--continue")
+            .then(|| Self::load(SAVE_PATH))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn save() -> SaveGame {
+        SaveGame {
+            version: SAVE_VERSION,
+            seed: 87,
+            floors: vec![
+                FloorState {
+                    stage: Some(1),
+                    elapsed: 0.5,
+                },
+                FloorState {
+                    stage: None,
+                    elapsed: 0.0,
+                },
+            ],
+            position: Vec3::new(4.0, -12.2, 0.5),
+            yaw: 1.25,
+            pitch: -0.3,
+            kill_timer: 0.0,
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let save = save();
+        let parsed = SaveGame::parse(&save.to_ron().unwrap()).unwrap();
+        assert_eq!(parsed, save);
+    }
+
+    #[test]
+    fn round_trip_through_file() {
+        let path = std::env::temp_dir().join(format!("scp087b_save_{}.ron", std::process::id()));
+        let save = save();
+
+        save.save(&path).unwrap();
+        let loaded = SaveGame::load(&path);
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded.unwrap(), save);
+    }
+
+    #[test]
+    fn rejects_corrupted_save() {
+        let contents = save().to_ron().unwrap();
+        let truncated = &contents[..contents.len() / 2];
+
+        assert!(matches!(
+            SaveGame::parse(truncated),
+            Err(SaveError::Corrupted(_))
+        ));
+        assert!(matches!(
+            SaveGame::parse("not a save"),
+            Err(SaveError::Corrupted(_))
+        ));
+    }
+
+    #[test]
+    fn rejects_older_version() {
+        let old = SaveGame {
+            version: SAVE_VERSION - 1,
+            ..save()
+        };
+
+        assert!(matches!(
+            SaveGame::parse(&old.to_ron().unwrap()),
+            Err(SaveError::UnsupportedVersion { found }) if found == SAVE_VERSION - 1
+        ));
+    }
+
+    #[test]
+    fn missing_file_is_an_io_error() {
+        assert!(matches!(
+            SaveGame::load("does/not/exist.ron"),
+            Err(SaveError::Io(_))
+        ));
+    }
+}
@@ -0,0 +1,188 @@
+use super::resources::{FloorState, SaveGame, SAVE_PATH, SAVE_VERSION};
+use crate::{
+    game::{
+        map::resources::{Map, MapSeed},
+        player::{
+            components::{Player, PlayerInput},
+            resources::PlayerAction,
+        },
+    },
+    AppState,
+};
+use avian3d::prelude::*;
+use bevy::prelude::*;
+use leafwing_input_manager::prelude::*;
+
+/// Goes straight to the game when started with `--continue`
+pub fn save_skip_menu(mut next_app_state: ResMut<NextState<AppState>>) {
+    next_app_state.set(AppState::Preload);
+}
+
+pub fn load_save_seed(save: Option<Res<SaveGame>>, mut seed: ResMut<MapSeed>) {
+    if let Some(save) = save {
+        seed.0 = save.seed;
+    }
+}
+
+pub fn apply_save(
+    save: Option<Res<SaveGame>>,
+    mut commands: Commands,
+    mut map: ResMut<Map>,
+    mut query: Query<(&mut Player, &mut PlayerInput, &mut Position, &mut Transform)>,
+) {
+    let Some(save) = save else {
+        return;
+    };
+
+    if save.floors.len() == map.floors.len() {
+        for (floor, state) in map.floors.iter_mut().zip(&save.floors) {
+            match state.stage {
+                // The walls, enemies and lighting of an event under way are not saved,
+                // so it starts over
+                Some(stage) if stage > 0 => {
+                    floor.stage = Some(0);
+                    floor.elapsed = 0.0;
+                }
+                stage => {
+                    floor.stage = stage;
+                    floor.elapsed = state.elapsed;
+                }
+            }
+        }
+    } else {
+        warn!("The save file does not match the map, floor events were not restored");
+    }
+
+    for (mut player, mut input, mut position, mut transform) in &mut query {
+        player.kill_timer = save.kill_timer;
+        input.yaw = save.yaw;
+        input.pitch = save.pitch;
+        position.0 = save.position;
+        transform.translation = save.position;
+    }
+
+    commands.remove_resource::<SaveGame>();
+}
+
+pub fn quicksave(
+    map: Res<Map>,
+    seed: Res<MapSeed>,
+    query: Query<(
+        &Player,
+        &PlayerInput,
+        &Transform,
+        &ActionState<PlayerAction>,
+    )>,
+) {
+    if let Ok((player, input, transform, action_state)) = query.get_single() {
+        if !action_state.just_pressed(&PlayerAction::QuickSave) {
+            return;
+        }
+
+        let save = SaveGame {
+            version: SAVE_VERSION,
+            seed: seed.0,
+            floors: map
+                .floors
+                .iter()
+                .map(|floor| FloorState {
+                    stage: floor.stage,
+                    elapsed: floor.elapsed,
+                })
+                .collect(),
+            position: transform.translation,
+            yaw: input.yaw,
+            pitch: input.pitch,
+            kill_timer: player.kill_timer,
+        };
+
+        match save.save(SAVE_PATH) {
+            Ok(()) => info!("Saved the game to {SAVE_PATH}"),
+            Err(err) => error!("Could not save the game: {err}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{
+        map::{
+            components::FloorScoped,
+            resources::{FloorAction, FloorPoints},
+            systems::update_floors,
+        },
+        player::components::{Lighter, PlayerCamera},
+        testing::test_app,
+    };
+    use bevy::ecs::system::RunSystemOnce;
+
+    #[test]
+    fn event_under_way_starts_over_on_load() {
+        let mut app = test_app();
+        app.add_systems(Update, update_floors);
+
+        let mut map = Map::default();
+        map.floors[2].action = FloorAction::Flash;
+        map.floors[3].action = FloorAction::Trap;
+        map.floors[3].stage = Some(0);
+        map.floors[4].stage = Some(0);
+
+        let mut floors = vec![
+            FloorState {
+                stage: None,
+                elapsed: 0.0,
+            };
+            map.floors.len()
+        ];
+        floors[3] = FloorState {
+            stage: Some(1),
+            elapsed: 2.5,
+        };
+        floors[4] = FloorState {
+            stage: Some(0),
+            elapsed: 1.5,
+        };
+
+        let start = FloorPoints::new(3).start;
+        app.insert_resource(map).insert_resource(SaveGame {
+            version: SAVE_VERSION,
+            seed: 87,
+            floors,
+            position: start,
+            yaw: 0.0,
+            pitch: 0.0,
+            kill_timer: 0.0,
+        });
+
+        let world = app.world_mut();
+        world.spawn((
+            Player {
+                floor_index: 3,
+                ..default()
+            },
+            PlayerInput::default(),
+            Position::default(),
+            Transform::default(),
+        ));
+        world.spawn((
+            PlayerCamera::default(),
+            Transform::from_translation(start),
+            GlobalTransform::from_translation(start),
+        ));
+        world.spawn(Lighter::default());
+        world.run_system_once(apply_save);
+
+        let floors = &app.world().resource::<Map>().floors;
+        assert_eq!(floors[2].stage, None);
+        assert_eq!((floors[3].stage, floors[3].elapsed), (Some(0), 0.0));
+        assert_eq!((floors[4].stage, floors[4].elapsed), (Some(0), 1.5));
+        assert!(!app.world().contains_resource::<SaveGame>());
+
+        // The trap walls the way again, as nothing of the first run was restored
+        app.update();
+        assert_eq!(app.world().resource::<Map>().floors[3].stage, Some(1));
+        let world = app.world_mut();
+        assert_eq!(world.query::<&FloorScoped>().iter(world).count(), 1);
+    }
+}
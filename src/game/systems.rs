@@ -1,15 +1,22 @@
 use super::{
-    enemy::components::{Enemy, EnemyAnimations},
-    glimpse::components::Glimpse,
+    enemy::{
+        archetypes::EnemyArchetype,
+        components::{Enemy, EnemyAi, EnemyAnimations},
+    },
     map::{
         components::{FloorLabel, FloorLabelUi},
-        resources::{FloorAction, Map},
+        resources::{Map, MapSeed},
+    },
+    player::{
+        bundles::PlayerBundle,
+        components::{Lighter, Player, PlayerCamera},
+        resources::Controls,
     },
-    player::{bundles::PlayerBundle, components::PlayerCamera},
 };
-use crate::resources::{AudioAssets, MapAssets};
+use crate::{resources::MapAssets, settings::Settings, AppState};
 use avian3d::prelude::*;
 use bevy::{
+    gltf::GltfAssetLabel,
     prelude::*,
     render::{
         camera::RenderTarget,
@@ -18,25 +25,18 @@ use bevy::{
         },
     },
 };
-use bevy_mod_billboard::prelude::*;
 use bevy_rand::prelude::*;
+use leafwing_input_manager::prelude::*;
 use rand::prelude::*;
 
 pub fn spawn_map(
     map_assets: Res<MapAssets>,
-    audio_assets: Res<AudioAssets>,
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
     mut images: ResMut<Assets<Image>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
-    mut rng: ResMut<GlobalEntropy<WyRand>>,
+    seed: Res<MapSeed>,
 ) {
-    // Music
-    commands.spawn(AudioBundle {
-        source: audio_assets.music.clone(),
-        settings: PlaybackSettings::LOOP,
-    });
-
     // Door
     commands.spawn((
         PbrBundle {
@@ -54,9 +54,12 @@ pub fn spawn_map(
         },
         Collider::cuboid(1.0, 2.0, 1.0),
         RigidBody::Static,
+        StateScoped(AppState::Game),
     ));
 
     // Generate Map
+    info!("Map seed: {}", seed.0);
+    let mut rng = WyRand::seed_from_u64(seed.0);
     let mut map = Map::default();
     map.generate(&mut rng);
 
@@ -89,15 +92,18 @@ pub fn spawn_map(
     let image_handle = images.add(image);
 
     let texture_camera = commands
-        .spawn(Camera2dBundle {
-            camera: Camera {
-                // render before the "main pass" camera
-                order: -1,
-                target: RenderTarget::Image(image_handle.clone()),
+        .spawn((
+            Camera2dBundle {
+                camera: Camera {
+                    // render before the "main pass" camera
+                    order: -1,
+                    target: RenderTarget::Image(image_handle.clone()),
+                    ..default()
+                },
                 ..default()
             },
-            ..default()
-        })
+            StateScoped(AppState::Game),
+        ))
         .id();
 
     commands
@@ -116,6 +122,7 @@ pub fn spawn_map(
                 ..default()
             },
             TargetCamera(texture_camera),
+            StateScoped(AppState::Game),
         ))
         .with_children(|parent| {
             parent.spawn((
@@ -139,6 +146,7 @@ pub fn spawn_map(
         },
         ColliderConstructorHierarchy::new(Some(ColliderConstructor::TrimeshFromMesh)),
         RigidBody::Static,
+        StateScoped(AppState::Game),
     ));
 
     commands.spawn((
@@ -152,133 +160,129 @@ pub fn spawn_map(
             ..default()
         },
         FloorLabel,
+        StateScoped(AppState::Game),
     ));
 
     commands.insert_resource(map);
 }
 
-pub fn spawn_player(mut commands: Commands) {
+pub fn spawn_player(mut commands: Commands, settings: Res<Settings>, controls: Res<Controls>) {
     // Player
     commands.spawn((
         Name::new("Player"),
         Collider::capsule(0.3, 1.0),
-        Friction::ZERO.with_combine_rule(CoefficientCombine::Min),
-        Restitution::ZERO.with_combine_rule(CoefficientCombine::Min),
-        RigidBody::Dynamic,
-        LockedAxes::ROTATION_LOCKED,
-        GravityScale(1.0),
+        // Ground check, cast from the bottom of the capsule
+        ShapeCaster::new(
+            Collider::sphere(0.29),
+            Vec3::NEG_Y * 0.5,
+            Quat::default(),
+            Dir3::NEG_Y,
+        )
+        .with_max_time_of_impact(0.1),
+        RigidBody::Kinematic,
         Position::from_xyz(-1.5, -1.0, 0.5),
         TransformBundle::default(),
-        PlayerBundle::default(),
-    ));
-
-    // Player Camera
-    commands.spawn((
-        Camera3dBundle {
-            transform: Transform::from_xyz(-1.5, -1.0, 0.5),
-            projection: Projection::Perspective(PerspectiveProjection {
-                far: 1.0,
+        PlayerBundle {
+            player: Player {
+                mouse_sensitivity: settings.mouse_sensitivity,
                 ..default()
-            }),
-            ..default()
-        },
-        FogSettings {
-            color: Color::srgb(0.0, 0.0, 0.0),
-            falloff: FogFalloff::Linear {
-                start: 1.0,
-                end: 2.5,
             },
+            input_bundle: InputManagerBundle::with_map(controls.input_map()),
             ..default()
         },
-        PlayerCamera::default(),
-        SpatialListener::new(4.0),
+        StateScoped(AppState::Game),
     ));
-}
-
-pub fn spawn_glimpses(
-    map: Res<Map>,
-    map_assets: Res<MapAssets>,
-    mut commands: Commands,
-    mut meshes: ResMut<Assets<Mesh>>,
-    mut rng: ResMut<GlobalEntropy<WyRand>>,
-) {
-    let glimpse_mesh = meshes.add(Rectangle::new(0.6, 0.6));
-
-    // TODO: Maybe include this in the pooling?
-    for (i, floor) in map.floors.iter().enumerate() {
-        if floor.action != FloorAction::Steps || rng.gen_range(1..7) != 1 {
-            continue;
-        }
-
-        let floor_y = -((i as f32 - 1.0) * 2.0 + 1.0);
-        let start_x = 0.8;
-        let end_x = 7.2;
-        let floor_z = if i % 2 == 0 {
-            6.55 // Even index
-        } else {
-            0.3 // Odd index
-        };
 
-        let floor_x = rng.gen_range(start_x..end_x);
-
-        let glimpse_texture = map_assets.glimpse_textures[rng.gen_range(0..1)].clone();
-
-        commands.spawn((
-            BillboardTextureBundle {
-                transform: Transform::from_xyz(floor_x, floor_y, floor_z),
-                texture: BillboardTextureHandle(glimpse_texture.clone()),
-                mesh: BillboardMeshHandle(glimpse_mesh.clone()),
+    // Player Camera
+    commands
+        .spawn((
+            Camera3dBundle {
+                transform: Transform::from_xyz(-1.5, -1.0, 0.5),
+                projection: Projection::Perspective(PerspectiveProjection {
+                    fov: settings.fov.to_radians(),
+                    far: 1.0,
+                    ..default()
+                }),
+                ..default()
+            },
+            FogSettings {
+                color: Color::srgb(0.0, 0.0, 0.0),
+                falloff: FogFalloff::Linear {
+                    start: 1.0,
+                    end: settings.fog_distance,
+                },
                 ..default()
             },
-            Glimpse,
-        ));
-    }
+            PlayerCamera::default(),
+            SpatialListener::new(4.0),
+            StateScoped(AppState::Game),
+        ))
+        .with_children(|parent| {
+            // Lighter, held low and to the right
+            parent.spawn((
+                PointLightBundle {
+                    point_light: PointLight {
+                        color: Color::srgb(1.0, 0.7, 0.4),
+                        intensity: 0.0,
+                        range: 4.0,
+                        ..default()
+                    },
+                    transform: Transform::from_xyz(0.15, -0.15, -0.3),
+                    ..default()
+                },
+                Lighter::default(),
+            ));
+        });
 }
 
+/// Spawns an enemy of the given archetype, `speed` overrides its top speed
 pub fn spawn_enemy(
-    map_assets: &Res<MapAssets>,
+    archetype: &EnemyArchetype,
     commands: &mut Commands,
-    graphs: &mut ResMut<Assets<AnimationGraph>>,
+    asset_server: &AssetServer,
+    graphs: &mut Assets<AnimationGraph>,
     position: Vec3,
-    speed: f32,
+    speed: Option<f32>,
 ) -> Entity {
-    // Build the animation graph
-    let mut graph = AnimationGraph::new();
-    let animations = graph
-        .add_clips(map_assets.mental_animations.clone(), 1.0, graph.root)
-        .collect();
-
-    // Insert a resource with the current scene information
-    let graph = graphs.add(graph);
+    let model = &archetype.model;
+    let animations = EnemyAnimations::new(
+        &archetype.animations,
+        |index| asset_server.load(GltfAssetLabel::Animation(index).from_asset(model.clone())),
+        graphs,
+    );
 
     // Enemy
-    commands
-        .spawn((
-            Name::new("Enemy"),
-            SpatialBundle::default(),
-            Collider::capsule(0.3, 1.0),
-            Friction::ZERO.with_combine_rule(CoefficientCombine::Min),
-            Restitution::ZERO.with_combine_rule(CoefficientCombine::Min),
-            RigidBody::Dynamic,
-            LockedAxes::new().lock_rotation_x().lock_rotation_z(),
-            GravityScale(1.0),
-            Position::new(position),
-            Enemy { speed },
-            EnemyAnimations {
-                animations,
-                graph: graph.clone(),
+    let mut enemy = commands.spawn((
+        Name::new("Enemy"),
+        SpatialBundle::default(),
+        Collider::capsule(archetype.collider.radius, archetype.collider.length),
+        Friction::ZERO.with_combine_rule(CoefficientCombine::Min),
+        Restitution::ZERO.with_combine_rule(CoefficientCombine::Min),
+        RigidBody::Dynamic,
+        LockedAxes::new().lock_rotation_x().lock_rotation_z(),
+        GravityScale(1.0),
+        Position::new(position),
+        Enemy {
+            speed: speed.unwrap_or(archetype.speed),
+            kill_radius: archetype.kill_radius,
+        },
+        EnemyAi::new(archetype.behaviour),
+        archetype.cues,
+        animations,
+        StateScoped(AppState::Game),
+    ));
+
+    enemy.with_children(|parent| {
+        parent.spawn(SceneBundle {
+            scene: asset_server.load(GltfAssetLabel::Scene(0).from_asset(model.clone())),
+            transform: Transform {
+                translation: archetype.model_offset,
+                rotation: Quat::from_rotation_y(f32::to_radians(180.0)),
+                scale: Vec3::splat(archetype.scale),
             },
-        ))
-        .with_children(|parent| {
-            parent.spawn(SceneBundle {
-                scene: map_assets.mental_model.clone(),
-                transform: Transform {
-                    translation: Vec3::Y * -0.7,
-                    rotation: Quat::from_rotation_y(f32::to_radians(180.0)),
-                    scale: Vec3::splat(0.17),
-                },
-                ..default()
-            });
-        })
-        .id()
+            ..default()
+        });
+    });
+
+    enemy.id()
 }
@@ -0,0 +1,102 @@
+//! Headless app for driving the game systems in tests
+
+use super::{
+    enemy::{archetypes::EnemyArchetypes, resources::EnemyRegistry},
+    lighting::resources::{Lighting, LightingState},
+    map::events::FloorEvents,
+};
+use crate::resources::{AudioAssets, MapAssets};
+use bevy::{asset::LoadState, gltf::Gltf, prelude::*, time::TimeUpdateStrategy};
+use bevy_common_assets::ron::RonAssetPlugin;
+use bevy_rand::prelude::*;
+use std::time::Duration;
+
+/// Length of a frame of the test app
+pub const FRAME: f32 = 1.0 / 60.0;
+
+/// App with the resources the game systems need, but no window, renderer or audio output.
+/// The asset collections are stubs, only the scripts are loaded from `assets`.
+pub fn test_app() -> App {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        AssetPlugin::default(),
+        EntropyPlugin::<WyRand>::with_seed(87u64.to_ne_bytes()),
+        RonAssetPlugin::<FloorEvents>::new(&["floors.ron"]),
+        RonAssetPlugin::<EnemyArchetypes>::new(&["archetypes.ron"]),
+    ))
+    .init_asset::<Gltf>()
+    .init_asset::<Scene>()
+    .init_asset::<AnimationClip>()
+    .init_asset::<AnimationGraph>()
+    .init_asset::<Mesh>()
+    .init_asset::<StandardMaterial>()
+    .init_asset::<Image>()
+    .init_asset::<AudioSource>()
+    .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f32(
+        FRAME,
+    )))
+    .insert_resource(Lighting::new(LightingState {
+        brightness: 80.0,
+        color: Color::WHITE,
+        fog_distance: 20.0,
+    }))
+    .init_resource::<AmbientLight>()
+    .init_resource::<EnemyRegistry>()
+    .insert_resource(stub_audio_assets());
+
+    let floor_events = load(&mut app, "map/events.floors.ron");
+    let enemy_archetypes = load(&mut app, "map/enemies.archetypes.ron");
+    app.insert_resource(MapAssets {
+        floor_events,
+        enemy_archetypes,
+        ..default()
+    });
+
+    app
+}
+
+/// Loads an asset from `assets`, updating the app until it is ready
+pub fn load<A: Asset>(app: &mut App, path: &'static str) -> Handle<A> {
+    let handle = app.world().resource::<AssetServer>().load(path);
+
+    loop {
+        match app.world().resource::<AssetServer>().load_state(&handle) {
+            LoadState::Loaded => return handle,
+            LoadState::Failed(err) => panic!("could not load {path}: {err}"),
+            _ => {
+                std::thread::sleep(Duration::from_millis(1));
+                app.update();
+            }
+        }
+    }
+}
+
+/// Audio assets with a distinct handle for every sound the floor events play,
+/// so tests can tell them apart
+pub fn stub_audio_assets() -> AudioAssets {
+    let mut next = 0;
+    let mut handle = || {
+        next += 1;
+        Handle::weak_from_u128(next)
+    };
+
+    AudioAssets {
+        step_sound: handle(),
+        loud_step_sound: handle(),
+        horror_sfx: vec![handle(), handle(), handle()],
+        roar_sfx: handle(),
+        breath_sfx: handle(),
+        stone_sfx: handle(),
+        radio_sfx: vec![handle(), handle(), handle(), handle()],
+        fire_off: handle(),
+        ..default()
+    }
+}
+
+/// Runs the app for `secs` seconds of game time
+pub fn run_for(app: &mut App, secs: f32) {
+    for _ in 0..(secs / FRAME).ceil() as usize {
+        app.update();
+    }
+}
@@ -0,0 +1,7 @@
+use bevy::prelude::*;
+
+#[derive(Component, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum GameOverButton {
+    Restart,
+    Quit,
+}
@@ -0,0 +1,22 @@
+use crate::AppState;
+use bevy::prelude::*;
+use systems::*;
+
+pub mod components;
+pub mod resources;
+mod systems;
+
+pub struct GameOverPlugin;
+
+impl Plugin for GameOverPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            // OnEnter State Systems
+            .add_systems(OnEnter(AppState::GameOver), spawn_game_over)
+            // Systems
+            .add_systems(
+                Update,
+                update_game_over.run_if(in_state(AppState::GameOver)),
+            );
+    }
+}
@@ -0,0 +1,10 @@
+use bevy::prelude::*;
+
+/// How the last descent ended, shown on the death screen
+#[derive(Resource, Clone, Debug)]
+pub struct DeathReport {
+    pub floor: usize,
+    pub seed: u64,
+    pub time_alive: f32,
+    pub message: Option<String>,
+}
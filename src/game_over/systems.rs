@@ -0,0 +1,98 @@
+use super::{components::GameOverButton, resources::DeathReport};
+use crate::{game::map::resources::MapSeed, resources::MapAssets, AppState};
+use bevy::{app::AppExit, prelude::*};
+
+pub fn spawn_game_over(
+    mut commands: Commands,
+    map_assets: Res<MapAssets>,
+    report: Option<Res<DeathReport>>,
+) {
+    let text_style = |font_size: f32| TextStyle {
+        font_size,
+        color: Color::WHITE,
+        font: map_assets.font.clone(),
+    };
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    flex_direction: FlexDirection::Column,
+                    row_gap: Val::Px(20.0),
+                    ..default()
+                },
+                background_color: Color::BLACK.into(),
+                ..default()
+            },
+            StateScoped(AppState::GameOver),
+        ))
+        .with_children(|parent| {
+            if let Some(report) = &report {
+                if let Some(message) = &report.message {
+                    parent.spawn(TextBundle::from_section(message, text_style(45.0)));
+                }
+
+                let minutes = report.time_alive as u32 / 60;
+                let seconds = report.time_alive as u32 % 60;
+
+                for line in [
+                    format!("Floor reached: {}", report.floor),
+                    format!("Time alive: {minutes}:{seconds:02}"),
+                    format!("Seed: {}", report.seed),
+                ] {
+                    parent.spawn(TextBundle::from_section(line, text_style(30.0)));
+                }
+            }
+
+            for (button, label) in [
+                (GameOverButton::Restart, "Restart"),
+                (GameOverButton::Quit, "Quit"),
+            ] {
+                parent
+                    .spawn((
+                        ButtonBundle {
+                            style: Style {
+                                padding: UiRect::axes(Val::Px(20.0), Val::Px(5.0)),
+                                ..default()
+                            },
+                            background_color: Color::NONE.into(),
+                            ..default()
+                        },
+                        button,
+                    ))
+                    .with_children(|parent| {
+                        parent.spawn(TextBundle::from_section(label, text_style(35.0)));
+                    });
+            }
+        });
+
+    commands.spawn((Camera2dBundle::default(), StateScoped(AppState::GameOver)));
+}
+
+pub fn update_game_over(
+    mut commands: Commands,
+    mut query: Query<(&Interaction, &GameOverButton, &mut BackgroundColor), Changed<Interaction>>,
+    mut next_app_state: ResMut<NextState<AppState>>,
+    mut app_exit: EventWriter<AppExit>,
+) {
+    for (interaction, button, mut background_color) in &mut query {
+        match interaction {
+            Interaction::Pressed => match button {
+                GameOverButton::Restart => {
+                    commands.insert_resource(MapSeed::default());
+                    commands.remove_resource::<DeathReport>();
+                    next_app_state.set(AppState::Game);
+                }
+                GameOverButton::Quit => {
+                    app_exit.send(AppExit::Success);
+                }
+            },
+            Interaction::Hovered => *background_color = Color::srgb(0.2, 0.0, 0.0).into(),
+            Interaction::None => *background_color = Color::NONE.into(),
+        }
+    }
+}
@@ -4,12 +4,18 @@ use bevy_asset_loader::prelude::*;
 use bevy_mod_billboard::prelude::*;
 use bevy_rand::prelude::*;
 use game::GamePlugin;
+use game_over::GameOverPlugin;
+use menu::MenuPlugin;
 use preload::PreloadPlugin;
 use resources::{AudioAssets, MapAssets};
+use settings::SettingsPlugin;
 
 mod game;
+mod game_over;
+mod menu;
 mod preload;
 mod resources;
+mod settings;
 
 fn main() {
     App::new()
@@ -23,7 +29,13 @@ fn main() {
             ..default()
         }))
         // SCP-087-B Redux Plugins
-        .add_plugins((PreloadPlugin, GamePlugin))
+        .add_plugins((
+            SettingsPlugin,
+            MenuPlugin,
+            PreloadPlugin,
+            GamePlugin,
+            GameOverPlugin,
+        ))
         // Other Plugins
         .add_plugins((
             EntropyPlugin::<WyRand>::default(),
@@ -32,9 +44,10 @@ fn main() {
             BillboardPlugin,
         ))
         .init_state::<AppState>()
+        .enable_state_scoped_entities::<AppState>()
         .add_loading_state(
             LoadingState::new(AppState::None)
-                .continue_to_state(AppState::Preload)
+                .continue_to_state(AppState::MainMenu)
                 .load_collection::<AudioAssets>()
                 .load_collection::<MapAssets>(),
         )
@@ -45,6 +58,10 @@ fn main() {
 enum AppState {
     #[default]
     None,
+    MainMenu,
+    Options,
+    Controls,
     Preload,
     Game,
+    GameOver,
 }
@@ -0,0 +1,115 @@
+use crate::{game::player::resources::PlayerAction, settings::Settings};
+use bevy::prelude::*;
+
+#[derive(Component, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum MenuButton {
+    NewGame,
+    Continue,
+    Options,
+    Controls,
+    Quit,
+    Back,
+}
+
+#[derive(Component)]
+pub struct SeedField;
+
+#[derive(Component, Clone, Copy)]
+pub struct OptionButton {
+    pub option: GameOption,
+    pub direction: f32,
+}
+
+#[derive(Component, Clone, Copy)]
+pub struct OptionValue(pub GameOption);
+
+#[derive(Component, Clone, Copy)]
+pub struct RebindButton(pub PlayerAction);
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum GameOption {
+    MouseSensitivity,
+    MasterVolume,
+    MusicVolume,
+    SfxVolume,
+    VoiceVolume,
+    Fov,
+    FogDistance,
+}
+
+impl GameOption {
+    pub const ALL: [Self; 7] = [
+        Self::MouseSensitivity,
+        Self::MasterVolume,
+        Self::MusicVolume,
+        Self::SfxVolume,
+        Self::VoiceVolume,
+        Self::Fov,
+        Self::FogDistance,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::MouseSensitivity => "Mouse Sensitivity",
+            Self::MasterVolume => "Master Volume",
+            Self::MusicVolume => "Music Volume",
+            Self::SfxVolume => "SFX Volume",
+            Self::VoiceVolume => "Voice Volume",
+            Self::Fov => "Field of View",
+            Self::FogDistance => "Fog Distance",
+        }
+    }
+
+    /// Step, minimum and maximum of the option
+    fn bounds(&self) -> (f32, f32, f32) {
+        match self {
+            Self::MouseSensitivity => (0.0005, 0.0005, 0.01),
+            Self::MasterVolume | Self::MusicVolume | Self::SfxVolume | Self::VoiceVolume => {
+                (0.1, 0.0, 1.0)
+            }
+            Self::Fov => (5.0, 30.0, 110.0),
+            Self::FogDistance => (0.25, 1.5, 6.0),
+        }
+    }
+
+    pub fn value(&self, settings: &Settings) -> f32 {
+        match self {
+            Self::MouseSensitivity => settings.mouse_sensitivity,
+            Self::MasterVolume => settings.master_volume,
+            Self::MusicVolume => settings.music_volume,
+            Self::SfxVolume => settings.sfx_volume,
+            Self::VoiceVolume => settings.voice_volume,
+            Self::Fov => settings.fov,
+            Self::FogDistance => settings.fog_distance,
+        }
+    }
+
+    /// Moves the option one step in the given direction
+    pub fn change(&self, settings: &mut Settings, direction: f32) {
+        let (step, min, max) = self.bounds();
+        let value = (self.value(settings) + step * direction).clamp(min, max);
+
+        match self {
+            Self::MouseSensitivity => settings.mouse_sensitivity = value,
+            Self::MasterVolume => settings.master_volume = value,
+            Self::MusicVolume => settings.music_volume = value,
+            Self::SfxVolume => settings.sfx_volume = value,
+            Self::VoiceVolume => settings.voice_volume = value,
+            Self::Fov => settings.fov = value,
+            Self::FogDistance => settings.fog_distance = value,
+        }
+    }
+
+    pub fn format(&self, settings: &Settings) -> String {
+        let value = self.value(settings);
+
+        match self {
+            Self::MouseSensitivity => format!("{:.1}", value * 1000.0),
+            Self::MasterVolume | Self::MusicVolume | Self::SfxVolume | Self::VoiceVolume => {
+                format!("{:.0}%", value * 100.0)
+            }
+            Self::Fov => format!("{value:.0}"),
+            Self::FogDistance => format!("{value:.2}"),
+        }
+    }
+}
@@ -0,0 +1,45 @@
+use crate::AppState;
+use bevy::prelude::*;
+use resources::{Rebinding, SeedInput};
+use systems::*;
+
+pub mod components;
+pub mod resources;
+mod systems;
+
+pub struct MenuPlugin;
+
+impl Plugin for MenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(SeedInput::from_args())
+            .init_resource::<Rebinding>()
+            // OnEnter State Systems
+            .add_systems(OnEnter(AppState::MainMenu), spawn_main_menu)
+            .add_systems(OnEnter(AppState::Options), spawn_options)
+            .add_systems(OnEnter(AppState::Controls), spawn_controls)
+            // OnExit State Systems
+            .add_systems(OnExit(AppState::Options), save_settings)
+            .add_systems(OnExit(AppState::Controls), save_controls)
+            // Systems
+            .add_systems(
+                Update,
+                (update_main_menu, update_seed_input).run_if(in_state(AppState::MainMenu)),
+            )
+            .add_systems(
+                Update,
+                (update_options, update_option_values).run_if(in_state(AppState::Options)),
+            )
+            .add_systems(
+                Update,
+                (update_controls, update_rebind_labels).run_if(in_state(AppState::Controls)),
+            )
+            .add_systems(
+                Update,
+                highlight_buttons.run_if(
+                    in_state(AppState::MainMenu)
+                        .or_else(in_state(AppState::Options))
+                        .or_else(in_state(AppState::Controls)),
+                ),
+            );
+    }
+}
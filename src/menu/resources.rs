@@ -0,0 +1,32 @@
+use crate::game::{map::resources::MapSeed, player::resources::PlayerAction};
+use bevy::prelude::*;
+
+/// Digits typed into the seed field of the main menu
+#[derive(Resource, Default)]
+pub struct SeedInput(pub String);
+
+impl SeedInput {
+    pub fn from_args() -> Self {
+        Self(
+            MapSeed::from_args()
+                .map(|seed| seed.0.to_string())
+                .unwrap_or_default(),
+        )
+    }
+
+    /// The typed seed, or a random one if the field is empty
+    pub fn seed(&self) -> MapSeed {
+        if self.0.is_empty() {
+            return MapSeed::default();
+        }
+
+        self.0.parse().map(MapSeed).unwrap_or_else(|_| {
+            warn!("Seed {} is out of range, using a random one", self.0);
+            MapSeed::default()
+        })
+    }
+}
+
+/// Action waiting for a new key in the controls menu
+#[derive(Resource, Default)]
+pub struct Rebinding(pub Option<PlayerAction>);
@@ -0,0 +1,408 @@
+use super::{
+    components::*,
+    resources::{Rebinding, SeedInput},
+};
+use crate::{
+    game::{
+        player::resources::{Controls, PlayerAction},
+        save::resources::{SaveGame, SAVE_PATH},
+    },
+    resources::MapAssets,
+    settings::Settings,
+    AppState,
+};
+use bevy::{
+    app::AppExit,
+    input::keyboard::{Key, KeyboardInput},
+    prelude::*,
+};
+use std::path::Path;
+
+const MAX_SEED_DIGITS: usize = 20;
+
+fn text_style(map_assets: &MapAssets, font_size: f32) -> TextStyle {
+    TextStyle {
+        font_size,
+        color: Color::WHITE,
+        font: map_assets.font.clone(),
+    }
+}
+
+fn menu_root(state: AppState) -> impl Bundle {
+    (
+        NodeBundle {
+            style: Style {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(15.0),
+                ..default()
+            },
+            background_color: Color::BLACK.into(),
+            ..default()
+        },
+        StateScoped(state),
+    )
+}
+
+fn spawn_button(
+    parent: &mut ChildBuilder,
+    map_assets: &MapAssets,
+    button: impl Bundle,
+    label: &str,
+    font_size: f32,
+) {
+    parent
+        .spawn((
+            ButtonBundle {
+                style: Style {
+                    padding: UiRect::axes(Val::Px(20.0), Val::Px(5.0)),
+                    ..default()
+                },
+                background_color: Color::NONE.into(),
+                ..default()
+            },
+            button,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                label,
+                text_style(map_assets, font_size),
+            ));
+        });
+}
+
+fn seed_text(seed_input: &SeedInput) -> String {
+    if seed_input.0.is_empty() {
+        "Seed: random".to_string()
+    } else {
+        format!("Seed: {}_", seed_input.0)
+    }
+}
+
+pub fn spawn_main_menu(
+    mut commands: Commands,
+    map_assets: Res<MapAssets>,
+    seed_input: Res<SeedInput>,
+) {
+    let can_continue = Path::new(SAVE_PATH).exists();
+
+    commands
+        .spawn(menu_root(AppState::MainMenu))
+        .with_children(|parent| {
+            parent.spawn(ImageBundle {
+                style: Style {
+                    width: Val::Percent(30.0),
+                    height: Val::Percent(24.0),
+                    ..default()
+                },
+                image: map_assets.scp_logo.clone().into(),
+                ..default()
+            });
+
+            spawn_button(parent, &map_assets, MenuButton::NewGame, "New Game", 35.0);
+            if can_continue {
+                spawn_button(parent, &map_assets, MenuButton::Continue, "Continue", 35.0);
+            }
+            parent.spawn((
+                TextBundle::from_section(seed_text(&seed_input), text_style(&map_assets, 25.0)),
+                SeedField,
+            ));
+            spawn_button(parent, &map_assets, MenuButton::Options, "Options", 35.0);
+            spawn_button(parent, &map_assets, MenuButton::Quit, "Quit", 35.0);
+        });
+
+    commands.spawn((Camera2dBundle::default(), StateScoped(AppState::MainMenu)));
+}
+
+pub fn update_main_menu(
+    mut commands: Commands,
+    query: Query<(&Interaction, &MenuButton), Changed<Interaction>>,
+    seed_input: Res<SeedInput>,
+    mut next_app_state: ResMut<NextState<AppState>>,
+    mut app_exit: EventWriter<AppExit>,
+) {
+    for (interaction, button) in &query {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+
+        match button {
+            MenuButton::NewGame => {
+                commands.insert_resource(seed_input.seed());
+                commands.remove_resource::<SaveGame>();
+                next_app_state.set(AppState::Preload);
+            }
+            MenuButton::Continue => match SaveGame::load(SAVE_PATH) {
+                Ok(save) => {
+                    commands.insert_resource(save);
+                    next_app_state.set(AppState::Preload);
+                }
+                Err(err) => error!("Could not continue the saved game: {err}"),
+            },
+            MenuButton::Options => next_app_state.set(AppState::Options),
+            MenuButton::Quit => {
+                app_exit.send(AppExit::Success);
+            }
+            MenuButton::Controls | MenuButton::Back => {}
+        }
+    }
+}
+
+pub fn update_seed_input(
+    mut events: EventReader<KeyboardInput>,
+    mut seed_input: ResMut<SeedInput>,
+    mut query: Query<&mut Text, With<SeedField>>,
+) {
+    for event in events.read() {
+        if !event.state.is_pressed() {
+            continue;
+        }
+
+        match &event.logical_key {
+            Key::Character(chars) => {
+                for digit in chars.chars().filter(char::is_ascii_digit) {
+                    if seed_input.0.len() < MAX_SEED_DIGITS {
+                        seed_input.0.push(digit);
+                    }
+                }
+            }
+            Key::Backspace => {
+                seed_input.0.pop();
+            }
+            _ => {}
+        }
+    }
+
+    if seed_input.is_changed() {
+        for mut text in &mut query {
+            text.sections[0].value = seed_text(&seed_input);
+        }
+    }
+}
+
+pub fn spawn_options(mut commands: Commands, map_assets: Res<MapAssets>, settings: Res<Settings>) {
+    commands
+        .spawn(menu_root(AppState::Options))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                "Options",
+                text_style(&map_assets, 45.0),
+            ));
+
+            for option in GameOption::ALL {
+                parent
+                    .spawn(NodeBundle {
+                        style: Style {
+                            width: Val::Px(600.0),
+                            align_items: AlignItems::Center,
+                            column_gap: Val::Px(10.0),
+                            ..default()
+                        },
+                        ..default()
+                    })
+                    .with_children(|parent| {
+                        parent.spawn(
+                            TextBundle::from_section(option.label(), text_style(&map_assets, 25.0))
+                                .with_style(Style {
+                                    flex_grow: 1.0,
+                                    ..default()
+                                }),
+                        );
+                        spawn_button(
+                            parent,
+                            &map_assets,
+                            OptionButton {
+                                option,
+                                direction: -1.0,
+                            },
+                            "<",
+                            25.0,
+                        );
+                        parent.spawn((
+                            TextBundle::from_section(
+                                option.format(&settings),
+                                text_style(&map_assets, 25.0),
+                            )
+                            .with_style(Style {
+                                width: Val::Px(80.0),
+                                justify_content: JustifyContent::Center,
+                                ..default()
+                            }),
+                            OptionValue(option),
+                        ));
+                        spawn_button(
+                            parent,
+                            &map_assets,
+                            OptionButton {
+                                option,
+                                direction: 1.0,
+                            },
+                            ">",
+                            25.0,
+                        );
+                    });
+            }
+
+            spawn_button(parent, &map_assets, MenuButton::Controls, "Controls", 35.0);
+            spawn_button(parent, &map_assets, MenuButton::Back, "Back", 35.0);
+        });
+
+    commands.spawn((Camera2dBundle::default(), StateScoped(AppState::Options)));
+}
+
+pub fn update_options(
+    o_query: Query<(&Interaction, &OptionButton), Changed<Interaction>>,
+    b_query: Query<(&Interaction, &MenuButton), Changed<Interaction>>,
+    mut settings: ResMut<Settings>,
+    mut next_app_state: ResMut<NextState<AppState>>,
+) {
+    for (interaction, button) in &o_query {
+        if *interaction == Interaction::Pressed {
+            button.option.change(&mut settings, button.direction);
+        }
+    }
+
+    for (interaction, button) in &b_query {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+
+        match button {
+            MenuButton::Controls => next_app_state.set(AppState::Controls),
+            MenuButton::Back => next_app_state.set(AppState::MainMenu),
+            _ => {}
+        }
+    }
+}
+
+pub fn update_option_values(settings: Res<Settings>, mut query: Query<(&mut Text, &OptionValue)>) {
+    if !settings.is_changed() {
+        return;
+    }
+
+    for (mut text, value) in &mut query {
+        text.sections[0].value = value.0.format(&settings);
+    }
+}
+
+pub fn save_settings(settings: Res<Settings>) {
+    settings.save();
+}
+
+fn rebind_text(controls: &Controls, rebinding: &Rebinding, action: PlayerAction) -> String {
+    let key = if rebinding.0 == Some(action) {
+        "press a key...".to_string()
+    } else {
+        controls.keys.get(&action).map_or_else(
+            || "unbound".to_string(),
+            |key| {
+                let name = format!("{key:?}");
+                name.strip_prefix("Key").unwrap_or(&name).to_string()
+            },
+        )
+    };
+
+    format!("{}: {key}", action.label())
+}
+
+pub fn spawn_controls(
+    mut commands: Commands,
+    map_assets: Res<MapAssets>,
+    controls: Res<Controls>,
+    mut rebinding: ResMut<Rebinding>,
+) {
+    rebinding.0 = None;
+
+    commands
+        .spawn(menu_root(AppState::Controls))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                "Controls",
+                text_style(&map_assets, 45.0),
+            ));
+
+            for action in PlayerAction::REBINDABLE {
+                spawn_button(
+                    parent,
+                    &map_assets,
+                    RebindButton(action),
+                    &rebind_text(&controls, &rebinding, action),
+                    25.0,
+                );
+            }
+
+            spawn_button(parent, &map_assets, MenuButton::Back, "Back", 35.0);
+        });
+
+    commands.spawn((Camera2dBundle::default(), StateScoped(AppState::Controls)));
+}
+
+pub fn update_controls(
+    keys: Res<ButtonInput<KeyCode>>,
+    r_query: Query<(&Interaction, &RebindButton), Changed<Interaction>>,
+    b_query: Query<(&Interaction, &MenuButton), Changed<Interaction>>,
+    mut controls: ResMut<Controls>,
+    mut rebinding: ResMut<Rebinding>,
+    mut next_app_state: ResMut<NextState<AppState>>,
+) {
+    if let Some(action) = rebinding.0 {
+        if let Some(&key) = keys.get_just_pressed().next() {
+            // Escape cancels the rebinding
+            if key != KeyCode::Escape {
+                controls.bind_key(action, key);
+            }
+            rebinding.0 = None;
+        }
+        return;
+    }
+
+    for (interaction, button) in &r_query {
+        if *interaction == Interaction::Pressed {
+            rebinding.0 = Some(button.0);
+        }
+    }
+
+    for (interaction, button) in &b_query {
+        if *interaction == Interaction::Pressed && *button == MenuButton::Back {
+            next_app_state.set(AppState::Options);
+        }
+    }
+}
+
+pub fn update_rebind_labels(
+    controls: Res<Controls>,
+    rebinding: Res<Rebinding>,
+    b_query: Query<(&RebindButton, &Children)>,
+    mut t_query: Query<&mut Text>,
+) {
+    if !controls.is_changed() && !rebinding.is_changed() {
+        return;
+    }
+
+    for (button, children) in &b_query {
+        for &child in children {
+            if let Ok(mut text) = t_query.get_mut(child) {
+                text.sections[0].value = rebind_text(&controls, &rebinding, button.0);
+            }
+        }
+    }
+}
+
+pub fn save_controls(controls: Res<Controls>) {
+    controls.save();
+}
+
+#[allow(clippy::type_complexity)]
+pub fn highlight_buttons(
+    mut query: Query<(&Interaction, &mut BackgroundColor), (Changed<Interaction>, With<Button>)>,
+) {
+    for (interaction, mut background_color) in &mut query {
+        *background_color = match interaction {
+            Interaction::Pressed | Interaction::Hovered => Color::srgb(0.2, 0.0, 0.0).into(),
+            Interaction::None => Color::NONE.into(),
+        };
+    }
+}
@@ -1,4 +1,4 @@
-use crate::{resources::MapAssets, AppState};
+use crate::{game::enemy::resources::EnemyModels, resources::MapAssets, AppState};
 use bevy::prelude::*;
 
 #[derive(Component)]
@@ -67,9 +67,12 @@ fn despawn_preload(mut commands: Commands, query: Query<Entity, With<PreloadComp
 
 fn update_preload(
     keys: Res<ButtonInput<KeyCode>>,
+    asset_server: Res<AssetServer>,
+    enemy_models: Res<EnemyModels>,
     mut next_app_state: ResMut<NextState<AppState>>,
 ) {
-    if keys.just_pressed(KeyCode::Space) {
+    // Waits for the enemy models, so the first enemy doesn't pop in
+    if keys.just_pressed(KeyCode::Space) && enemy_models.is_loaded(&asset_server) {
         next_app_state.set(AppState::Game);
     }
 }
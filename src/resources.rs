@@ -1,7 +1,9 @@
+use crate::game::{enemy::archetypes::EnemyArchetypes, map::events::FloorEvents};
 use bevy::prelude::*;
 use bevy_asset_loader::prelude::*;
 
 #[derive(AssetCollection, Resource)]
+#[cfg_attr(test, derive(Default))]
 #[allow(dead_code)]
 pub struct AudioAssets {
     #[asset(path = "audio/step.wav")]
@@ -22,6 +24,8 @@ pub struct AudioAssets {
     pub no_sfx: Handle<AudioSource>,
     #[asset(path = "audio/ambient", collection(typed))]
     pub ambient_sfx: Vec<Handle<AudioSource>>,
+    #[asset(path = "audio/behind.ogg")]
+    pub behind_sfx: Handle<AudioSource>,
     #[asset(path = "audio/dontlook.ogg")]
     pub dontlook_sfx: Handle<AudioSource>,
     #[asset(path = "audio/radio", collection(typed))]
@@ -37,6 +41,7 @@ pub struct AudioAssets {
 }
 
 #[derive(AssetCollection, Resource)]
+#[cfg_attr(test, derive(Default))]
 pub struct MapAssets {
     #[asset(path = "map/scp.jpg")]
     pub scp_logo: Handle<Image>,
@@ -74,12 +79,9 @@ pub struct MapAssets {
     #[asset(path = "map/glimpses", collection(typed))]
     pub glimpse_textures: Vec<Handle<Image>>,
 
-    #[asset(path = "map/mental.gltf#Scene0")]
-    pub mental_model: Handle<Scene>,
+    #[asset(path = "map/events.floors.ron")]
+    pub floor_events: Handle<FloorEvents>,
 
-    #[asset(
-        paths("map/mental.gltf#Animation10", "map/mental.gltf#Animation19"),
-        collection(typed)
-    )]
-    pub mental_animations: Vec<Handle<AnimationClip>>,
+    #[asset(path = "map/enemies.archetypes.ron")]
+    pub enemy_archetypes: Handle<EnemyArchetypes>,
 }
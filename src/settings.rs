@@ -0,0 +1,66 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::fs;
+
+pub const SETTINGS_PATH: &str = "settings.ron";
+
+#[derive(Resource, Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct Settings {
+    pub mouse_sensitivity: f32,
+    pub master_volume: f32,
+    pub music_volume: f32,
+    pub sfx_volume: f32,
+    pub voice_volume: f32,
+    /// Vertical field of view in degrees
+    pub fov: f32,
+    /// Distance at which the fog becomes fully opaque
+    pub fog_distance: f32,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            mouse_sensitivity: 0.003,
+            master_volume: 1.0,
+            music_volume: 1.0,
+            sfx_volume: 1.0,
+            voice_volume: 1.0,
+            fov: 45.0,
+            fog_distance: 2.5,
+        }
+    }
+}
+
+impl Settings {
+    /// Loads the settings file, falling back to the defaults if it is missing or broken
+    pub fn load() -> Self {
+        let Ok(contents) = fs::read_to_string(SETTINGS_PATH) else {
+            return Self::default();
+        };
+
+        ron::from_str(&contents).unwrap_or_else(|err| {
+            warn!("Could not read {SETTINGS_PATH}, using the default settings: {err}");
+            Self::default()
+        })
+    }
+
+    pub fn save(&self) {
+        match ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default()) {
+            Ok(contents) => {
+                if let Err(err) = fs::write(SETTINGS_PATH, contents) {
+                    error!("Could not save the settings: {err}");
+                }
+            }
+            Err(err) => error!("Could not save the settings: {err}"),
+        }
+    }
+}
+
+pub struct SettingsPlugin;
+
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Settings::load());
+    }
+}